@@ -0,0 +1,545 @@
+//! A reader and writer for Steam's binary VDF (KeyValues) format, which is
+//! used by shortcuts.vdf and, with a header per app, by appcache/appinfo.vdf
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MAP: u8 = 0x00;
+const STR: u8 = 0x01;
+const INT32: u8 = 0x02;
+const FLOAT: u8 = 0x03;
+const POINTER: u8 = 0x04;
+const WIDE_STR: u8 = 0x05;
+const COLOR: u8 = 0x06;
+const UINT64: u8 = 0x07;
+const END: u8 = 0x08;
+const INT64: u8 = 0x0a;
+const ALT_END: u8 = 0x0b;
+
+/// Magic numbers of the supported appinfo.vdf versions
+const APPINFO_V27: u32 = 0x0756_4427;
+const APPINFO_V28: u32 = 0x0756_4428;
+const APPINFO_V29: u32 = 0x0756_4429;
+
+/// A node of a binary VDF document; unlike text VDF, values are typed
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	Map(Vec<(String, Value)>),
+	Str(String),
+	WideStr(String),
+	Int32(i32),
+	Float(f32),
+	Pointer(u32),
+	Color(u32),
+	UInt64(u64),
+	Int64(i64)
+}
+
+impl Value {
+	/// Gets a child by key; keys are compared case-insensitively
+	pub fn get(&self, key: &str) -> Option<&Value> {
+		self.entries().iter()
+			.find(|(k, _)| k.eq_ignore_ascii_case(key))
+			.map(|(_, v)| v)
+	}
+
+	/// Follows a path of keys, e.g. `["appinfo", "common", "name"]`
+	pub fn get_path(&self, path: &[&str]) -> Option<&Value> {
+		path.iter().try_fold(self, |node, key| node.get(key))
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Value::Str(s) | Value::WideStr(s) => Some(s),
+			_ => None
+		}
+	}
+
+	/// Gets the value of any integer type
+	pub fn as_int(&self) -> Option<i64> {
+		match *self {
+			Value::Int32(i) => Some(i64::from(i)),
+			Value::Pointer(i) | Value::Color(i) => Some(i64::from(i)),
+			Value::UInt64(i) => Some(i as i64),
+			Value::Int64(i) => Some(i),
+			_ => None
+		}
+	}
+
+	pub fn entries(&self) -> &[(String, Value)] {
+		match self {
+			Value::Map(entries) => entries,
+			_ => &[]
+		}
+	}
+
+	/// Sets a child of a map, replacing an existing one with the same key
+	pub fn insert(&mut self, key: &str, value: Value) {
+		if let Value::Map(entries) = self {
+			match entries.iter_mut().find(|(k, _)| k.eq_ignore_ascii_case(key)) {
+				Some(entry) => entry.1 = value,
+				None => entries.push((key.to_string(), value))
+			}
+		}
+	}
+
+	/// Removes a child of a map, returning it if it existed
+	pub fn remove(&mut self, key: &str) -> Option<Value> {
+		match self {
+			Value::Map(entries) => {
+				let index = entries.iter().position(|(k, _)| k.eq_ignore_ascii_case(key))?;
+				Some(entries.remove(index).1)
+			},
+			_ => None
+		}
+	}
+}
+
+/// Parses a binary VDF document, such as shortcuts.vdf, into a map of its
+/// top-level entries
+pub fn parse(bytes: &[u8]) -> Result<Value, String> {
+	let mut reader = Reader { bytes, pos: 0, strings: None };
+
+	reader.entries().map(Value::Map)
+}
+
+/// Reads and parses a binary VDF file
+pub fn parse_file(path: &Path) -> Result<Value, String> {
+	let bytes = fs::read(path)
+		.map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+	parse(&bytes).map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+}
+
+/// Serializes the entries of a map into a binary VDF document
+pub fn to_bytes(value: &Value) -> Vec<u8> {
+	let mut bytes = Vec::new();
+
+	write_entries(&mut bytes, value.entries());
+	bytes
+}
+
+/// Writes a binary VDF file such as shortcuts.vdf, keeping a backup of the
+/// old one, which is returned
+///
+/// The new file replaces the old one only once it has been written
+/// completely, and only if it reads back as the same document.
+pub fn write_file(path: &Path, value: &Value) -> Result<Option<PathBuf>, String> {
+	let bytes = to_bytes(value);
+
+	if parse(&bytes).as_ref() != Ok(value) {
+		return Err(format!("Could not write {}: the document would not read back the same", path.display()));
+	}
+
+	let backup = if path.exists() {
+		let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+		let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+		// Backups made within the same second get a counter instead of replacing each other
+		let backup = (0..)
+			.map(|n| match n {
+				0 => path.with_file_name(format!("{}.{}.bak", name, now)),
+				n => path.with_file_name(format!("{}.{}-{}.bak", name, now, n))
+			})
+			.find(|backup| !backup.exists())
+			.unwrap();
+
+		fs::copy(path, &backup)
+			.map_err(|e| format!("Could not back up {}: {}", path.display(), e))?;
+
+		Some(backup)
+	} else {
+		None
+	};
+
+	let mut temp = path.as_os_str().to_owned();
+	temp.push(".tmp");
+
+	fs::write(&temp, bytes)
+		.and_then(|_| fs::rename(&temp, path))
+		.map_err(|e| format!("Could not write {}: {}", path.display(), e))?;
+
+	Ok(backup)
+}
+
+fn write_entries(bytes: &mut Vec<u8>, entries: &[(String, Value)]) {
+	for (key, value) in entries {
+		let kind = match value {
+			Value::Map(_) => MAP,
+			Value::Str(_) => STR,
+			Value::WideStr(_) => WIDE_STR,
+			Value::Int32(_) => INT32,
+			Value::Float(_) => FLOAT,
+			Value::Pointer(_) => POINTER,
+			Value::Color(_) => COLOR,
+			Value::UInt64(_) => UINT64,
+			Value::Int64(_) => INT64
+		};
+
+		bytes.push(kind);
+		write_cstring(bytes, key);
+
+		match value {
+			Value::Map(children) => write_entries(bytes, children),
+			Value::Str(s) => write_cstring(bytes, s),
+			Value::WideStr(s) => {
+				for unit in s.encode_utf16().chain(std::iter::once(0)) {
+					bytes.extend_from_slice(&unit.to_le_bytes());
+				}
+			},
+			Value::Int32(i) => bytes.extend_from_slice(&i.to_le_bytes()),
+			Value::Float(f) => bytes.extend_from_slice(&f.to_le_bytes()),
+			Value::Pointer(i) | Value::Color(i) => bytes.extend_from_slice(&i.to_le_bytes()),
+			Value::UInt64(i) => bytes.extend_from_slice(&i.to_le_bytes()),
+			Value::Int64(i) => bytes.extend_from_slice(&i.to_le_bytes())
+		}
+	}
+
+	bytes.push(END);
+}
+
+fn write_cstring(bytes: &mut Vec<u8>, s: &str) {
+	bytes.extend_from_slice(s.as_bytes());
+	bytes.push(0);
+}
+
+/// An app of Steam's local app info cache (appcache/appinfo.vdf)
+#[derive(Debug, Clone)]
+pub struct AppInfo {
+	pub id: u32,
+	/// When the info was last updated, in seconds since the Unix epoch
+	pub last_updated: u32,
+	pub change_number: u32,
+	/// The app's info, with everything under a top-level "appinfo" key
+	pub data: Value
+}
+
+impl AppInfo {
+	/// The app's name, e.g. "Euro Truck Simulator 2"
+	pub fn name(&self) -> Option<&str> {
+		self.data.get_path(&["appinfo", "common", "name"]).and_then(Value::as_str)
+	}
+
+	/// The app's type as Steam writes it, e.g. "Game", "DLC" or "Tool"
+	pub fn app_type(&self) -> Option<&str> {
+		self.data.get_path(&["appinfo", "common", "type"]).and_then(Value::as_str)
+	}
+}
+
+/// Parses the apps of an appinfo.vdf file (versions 27 to 29) for which
+/// `wanted` returns true; the others are skipped without being parsed
+pub fn parse_appinfo(bytes: &[u8], wanted: impl Fn(u32) -> bool) -> Result<Vec<AppInfo>, String> {
+	let mut reader = Reader { bytes, pos: 0, strings: None };
+	let magic = reader.u32()?;
+	let _universe = reader.u32()?;
+
+	match magic {
+		APPINFO_V27 | APPINFO_V28 => {},
+		// Keys are stored once in a table at the end of the file and
+		// referred to by their index
+		APPINFO_V29 => {
+			let offset = reader.u64()? as usize;
+			let mut table = Reader { bytes, pos: offset, strings: None };
+			let count = table.u32()?;
+			let strings = (0..count).map(|_| table.cstring()).collect::<Result<_, _>>()?;
+
+			reader.strings = Some(strings);
+		},
+		_ => return Err(format!("unsupported appinfo version {:#x}", magic))
+	}
+
+	let mut apps = Vec::new();
+
+	loop {
+		let id = reader.u32()?;
+
+		if id == 0 {
+			return Ok(apps);
+		}
+
+		let size = reader.u32()? as usize;
+		let end = reader.pos + size;
+
+		if wanted(id) {
+			let _info_state = reader.u32()?;
+			let last_updated = reader.u32()?;
+			let _access_token = reader.u64()?;
+			let _sha1 = reader.take(20)?;
+			let change_number = reader.u32()?;
+
+			if magic != APPINFO_V27 {
+				let _binary_sha1 = reader.take(20)?;
+			}
+
+			let data = Value::Map(reader.entries()?);
+
+			apps.push(AppInfo { id, last_updated, change_number, data });
+		}
+
+		reader.pos = end;
+	}
+}
+
+/// Reads the info of one app from an appinfo.vdf file
+pub fn read_appinfo(path: &Path, id: u32) -> Result<Option<AppInfo>, String> {
+	let bytes = fs::read(path)
+		.map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+	parse_appinfo(&bytes, |other| other == id)
+		.map(|apps| apps.into_iter().next())
+		.map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+}
+
+struct Reader<'a> {
+	bytes: &'a [u8],
+	pos: usize,
+	/// The key table of appinfo.vdf version 29 and later
+	strings: Option<Vec<String>>
+}
+
+impl Reader<'_> {
+	fn entries(&mut self) -> Result<Vec<(String, Value)>, String> {
+		let mut entries = Vec::new();
+
+		loop {
+			let kind = self.take(1)?[0];
+
+			if kind == END || kind == ALT_END {
+				return Ok(entries);
+			}
+
+			let key = self.key()?;
+
+			let value = match kind {
+				MAP => Value::Map(self.entries()?),
+				STR => Value::Str(self.cstring()?),
+				WIDE_STR => Value::WideStr(self.wide_string()?),
+				INT32 => Value::Int32(self.u32()? as i32),
+				FLOAT => Value::Float(f32::from_bits(self.u32()?)),
+				POINTER => Value::Pointer(self.u32()?),
+				COLOR => Value::Color(self.u32()?),
+				UINT64 => Value::UInt64(self.u64()?),
+				INT64 => Value::Int64(self.u64()? as i64),
+				_ => return Err(self.error(&format!("unknown value type {:#04x} for '{}'", kind, key)))
+			};
+
+			entries.push((key, value));
+		}
+	}
+
+	fn key(&mut self) -> Result<String, String> {
+		if self.strings.is_none() {
+			return self.cstring();
+		}
+
+		let index = self.u32()? as usize;
+
+		match self.strings.as_ref().and_then(|strings| strings.get(index)) {
+			Some(key) => Ok(key.clone()),
+			None => Err(self.error(&format!("unknown key index {}", index)))
+		}
+	}
+
+	fn take(&mut self, len: usize) -> Result<&[u8], String> {
+		let end = self.pos.checked_add(len).filter(|end| *end <= self.bytes.len())
+			.ok_or_else(|| self.error("unexpected end of data"))?;
+		let bytes = &self.bytes[self.pos..end];
+
+		self.pos = end;
+		Ok(bytes)
+	}
+
+	fn u32(&mut self) -> Result<u32, String> {
+		let bytes = self.take(4)?;
+
+		Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+	}
+
+	fn u64(&mut self) -> Result<u64, String> {
+		let low = u64::from(self.u32()?);
+		let high = u64::from(self.u32()?);
+
+		Ok(high << 32 | low)
+	}
+
+	fn cstring(&mut self) -> Result<String, String> {
+		let len = self.bytes[self.pos.min(self.bytes.len())..].iter()
+			.position(|b| *b == 0)
+			.ok_or_else(|| self.error("unterminated string"))?;
+		let s = String::from_utf8_lossy(self.take(len)?).into_owned();
+
+		self.pos += 1;
+		Ok(s)
+	}
+
+	fn wide_string(&mut self) -> Result<String, String> {
+		let mut units = Vec::new();
+
+		loop {
+			let bytes = self.take(2)?;
+			let unit = u16::from_le_bytes([bytes[0], bytes[1]]);
+
+			if unit == 0 {
+				return Ok(String::from_utf16_lossy(&units));
+			}
+
+			units.push(unit);
+		}
+	}
+
+	fn error(&self, message: &str) -> String {
+		format!("{} at byte {}", message, self.pos)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn map(entries: Vec<(&str, Value)>) -> Value {
+		Value::Map(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+	}
+
+	#[test]
+	fn round_trips_every_type() {
+		let value = map(vec![
+			("shortcuts", map(vec![
+				("0", map(vec![
+					("AppName", Value::Str("Some Game".to_string())),
+					("title", Value::WideStr("Ünïcödé ゲーム".to_string())),
+					("appid", Value::Int32(-123_456)),
+					("scale", Value::Float(1.5)),
+					("handle", Value::Pointer(0xdead_beef)),
+					("tint", Value::Color(0x00ff_8800)),
+					("steamid", Value::UInt64(76_561_197_960_287_931)),
+					("offset", Value::Int64(-9_000_000_000)),
+					("tags", map(vec![]))
+				]))
+			]))
+		]);
+
+		assert_eq!(parse(&to_bytes(&value)), Ok(value));
+	}
+
+	#[test]
+	fn writes_a_file_and_backs_up_the_old_one() {
+		let dir = std::env::temp_dir().join(format!("steamlet-binary-vdf-{}", std::process::id()));
+		fs::create_dir_all(&dir).unwrap();
+		let path = dir.join("shortcuts.vdf");
+
+		let old = map(vec![("shortcuts", map(vec![("0", map(vec![("AppName", Value::Str("Old".to_string()))]))]))]);
+		let new = map(vec![("shortcuts", map(vec![("0", map(vec![("AppName", Value::Str("New".to_string()))]))]))]);
+
+		assert_eq!(write_file(&path, &old), Ok(None));
+		let backup = write_file(&path, &new).unwrap().unwrap();
+
+		assert_eq!(parse_file(&path), Ok(new));
+		assert_eq!(parse_file(&backup), Ok(old));
+		assert!(!dir.join("shortcuts.vdf.tmp").exists());
+
+		fs::remove_dir_all(&dir).unwrap();
+	}
+
+	#[test]
+	fn parses_a_handmade_document() {
+		let bytes = b"\x00root\0\x01name\0Portal\0\x02id\0\x90\x01\0\0\x08\x08";
+		let value = parse(bytes).unwrap();
+
+		assert_eq!(value.get_path(&["root", "name"]).and_then(Value::as_str), Some("Portal"));
+		assert_eq!(value.get_path(&["ROOT", "id"]).and_then(Value::as_int), Some(400));
+	}
+
+	#[test]
+	fn fails_on_truncated_data() {
+		assert_eq!(parse(b"\x01name\0Port"), Err("unterminated string at byte 6".to_string()));
+		assert_eq!(parse(b"\x02id\0\x01\0"), Err("unexpected end of data at byte 4".to_string()));
+		assert_eq!(parse(b"\x09key\0"), Err("unknown value type 0x09 for 'key' at byte 5".to_string()));
+	}
+
+	/// Builds an app entry of an appinfo.vdf file around its binary VDF data
+	fn appinfo_entry(magic: u32, id: u32, data: &[u8]) -> Vec<u8> {
+		let mut header = Vec::new();
+		header.extend_from_slice(&2u32.to_le_bytes());
+		header.extend_from_slice(&1_700_000_000u32.to_le_bytes());
+		header.extend_from_slice(&0u64.to_le_bytes());
+		header.extend_from_slice(&[0; 20]);
+		header.extend_from_slice(&42u32.to_le_bytes());
+
+		if magic != APPINFO_V27 {
+			header.extend_from_slice(&[0; 20]);
+		}
+
+		let mut entry = Vec::new();
+		entry.extend_from_slice(&id.to_le_bytes());
+		entry.extend_from_slice(&((header.len() + data.len()) as u32).to_le_bytes());
+		entry.extend_from_slice(&header);
+		entry.extend_from_slice(data);
+		entry
+	}
+
+	#[test]
+	fn parses_appinfo_v27_and_v28() {
+		let data = to_bytes(&map(vec![("appinfo", map(vec![("common", map(vec![
+			("name", Value::Str("Portal".to_string())),
+			("type", Value::Str("Game".to_string()))
+		]))]))]));
+
+		for magic in [APPINFO_V27, APPINFO_V28] {
+			let mut bytes = Vec::new();
+			bytes.extend_from_slice(&magic.to_le_bytes());
+			bytes.extend_from_slice(&1u32.to_le_bytes());
+			bytes.extend(appinfo_entry(magic, 10, &data));
+			bytes.extend(appinfo_entry(magic, 400, &data));
+			bytes.extend_from_slice(&0u32.to_le_bytes());
+
+			let apps = parse_appinfo(&bytes, |id| id == 400).unwrap();
+
+			assert_eq!(apps.len(), 1);
+			assert_eq!(apps[0].id, 400);
+			assert_eq!(apps[0].last_updated, 1_700_000_000);
+			assert_eq!(apps[0].change_number, 42);
+			assert_eq!(apps[0].name(), Some("Portal"));
+			assert_eq!(apps[0].app_type(), Some("Game"));
+		}
+	}
+
+	/// Builds a version 29 appinfo.vdf file of one app, with the given keys
+	/// in its table
+	fn appinfo_v29(data: &[u8], keys: &[&str]) -> Vec<u8> {
+		let mut apps = appinfo_entry(APPINFO_V29, 400, data);
+		apps.extend_from_slice(&0u32.to_le_bytes());
+
+		let mut bytes = Vec::new();
+		bytes.extend_from_slice(&APPINFO_V29.to_le_bytes());
+		bytes.extend_from_slice(&1u32.to_le_bytes());
+		bytes.extend_from_slice(&((16 + apps.len()) as u64).to_le_bytes());
+		bytes.extend(apps);
+		bytes.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+
+		for key in keys {
+			write_cstring(&mut bytes, key);
+		}
+
+		bytes
+	}
+
+	#[test]
+	fn parses_the_v29_key_table() {
+		// Keys are indices into the table: 0 = appinfo, 1 = common, 2 = name, 3 = type
+		let data = b"\x00\0\0\0\0\x00\x01\0\0\0\x01\x02\0\0\0Portal\0\x01\x03\0\0\0Game\0\x08\x08\x08";
+		let apps = parse_appinfo(&appinfo_v29(data, &["appinfo", "common", "name", "type"]), |_| true).unwrap();
+
+		assert_eq!(apps.len(), 1);
+		assert_eq!(apps[0].name(), Some("Portal"));
+		assert_eq!(apps[0].app_type(), Some("Game"));
+
+		let error = parse_appinfo(&appinfo_v29(data, &["appinfo", "common"]), |_| true).unwrap_err();
+		assert!(error.starts_with("unknown key index 2"), "{}", error);
+	}
+
+	#[test]
+	fn rejects_other_appinfo_versions() {
+		assert_eq!(parse_appinfo(b"\x26\x44\x56\x07\x01\0\0\0", |_| true).unwrap_err(), "unsupported appinfo version 0x7564426");
+	}
+}
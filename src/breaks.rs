@@ -0,0 +1,100 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::config::Config;
+use crate::notify;
+use crate::session::{self, Session, SessionHook};
+
+/// Minutes until the next reminder when one is snoozed, unless configured
+const DEFAULT_SNOOZE: u64 = 10;
+
+/// How often break reminders are shown, in minutes of continuous play
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Breaks {
+	/// Minutes between reminders; 0 turns them off
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub every: Option<u64>,
+
+	/// Minutes until a snoozed reminder is shown again
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub snooze: Option<u64>
+}
+
+impl Breaks {
+	pub fn is_empty(&self) -> bool {
+		*self == Breaks::default()
+	}
+
+	/// Reads the global reminder settings from the 'breaks' section of the config
+	pub fn global(config: &Config) -> Breaks {
+		Breaks {
+			every: config.get_u64("breaks.every"),
+			snooze: config.get_u64("breaks.snooze")
+		}
+	}
+
+	/// Labeled values for printing with 'output::print_fields'
+	pub fn fields(&self) -> Vec<(&'static str, String)> {
+		let minutes = |value: Option<u64>| value.map_or("unset".to_string(), |m| format!("{}m", m));
+
+		vec![("Every", minutes(self.every)), ("Snooze", minutes(self.snooze))]
+	}
+}
+
+/// Sends a desktop notification every so often to take a break, which can be
+/// snoozed from the notification
+pub struct BreakHook {
+	every: Duration,
+	snooze: Duration,
+	next: Duration,
+	snoozed: Arc<AtomicBool>
+}
+
+impl BreakHook {
+	/// Builds the hook from an alias' settings, falling back to the global
+	/// ones, or returns None if reminders are off
+	pub fn new(breaks: &Breaks, global: &Breaks) -> Option<BreakHook> {
+		let every = breaks.every.or(global.every).filter(|m| *m > 0)?;
+		let snooze = breaks.snooze.or(global.snooze).unwrap_or(DEFAULT_SNOOZE);
+
+		Some(BreakHook {
+			every: Duration::from_secs(every * 60),
+			snooze: Duration::from_secs(snooze * 60),
+			next: Duration::from_secs(every * 60),
+			snoozed: Arc::new(AtomicBool::new(false))
+		})
+	}
+}
+
+impl SessionHook for BreakHook {
+	fn on_tick(&mut self, session: &Session) -> bool {
+		let played = session.played();
+
+		// A reminder snoozed since the last tick comes back sooner
+		if self.snoozed.swap(false, Ordering::SeqCst) {
+			self.next = played + self.snooze;
+		}
+
+		if played >= self.next {
+			self.next = played + self.every;
+
+			let message = format!("You have been playing {} for {}; time to stretch", session.name, session::format_duration(played));
+			let snooze = format!("Snooze {}", session::format_duration(self.snooze));
+			let snoozed = Arc::clone(&self.snoozed);
+
+			println!("{}", message);
+
+			// The notification blocks until it is closed, so it runs in the background
+			thread::spawn(move || {
+				if notify::desktop_with_action("Break reminder", &message, &snooze) {
+					snoozed.store(true, Ordering::SeqCst);
+				}
+			});
+		}
+
+		true
+	}
+}
@@ -0,0 +1,281 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use crate::config::Config;
+use crate::crash::LastSession;
+use crate::localconfig;
+use crate::playtime;
+use crate::session;
+use crate::steam;
+
+/// Environment variables and flags whose names contain any of these have
+/// their values left out of reports
+const SECRET_MARKERS: &[&str] = &["TOKEN", "KEY", "SECRET", "PASS", "COOKIE", "AUTH", "CREDENTIAL", "LOGIN"];
+
+/// Short flags that are commonly followed by a password
+const SECRET_SHORT_FLAGS: &[&str] = &["-p", "-pw"];
+
+/// Flags followed by more than one secret, e.g. '-login <user> <password>'
+const MULTI_VALUE_FLAGS: &[(&str, usize)] = &[("-login", 2), ("+login", 2)];
+
+/// Commands whose output describes the system
+const SYSTEM_COMMANDS: &[&[&str]] = &[
+	&["uname", "-a"],
+	&["lscpu"],
+	&["free", "-h"],
+	&["nvidia-smi"],
+	&["glxinfo", "-B"],
+	&["vulkaninfo", "--summary"]
+];
+
+/// Steam's own logs that are useful for launch and Proton problems
+const STEAM_LOGS: &[&str] = &["console-linux.txt", "console_log.txt", "compat_log.txt", "content_log.txt"];
+
+fn looks_secret(name: &str) -> bool {
+	let name = name.to_uppercase();
+
+	SECRET_MARKERS.iter().any(|marker| name.contains(marker))
+}
+
+/// Replaces the value of a "NAME=value" pair if its name looks secret
+fn redact(var: &str) -> String {
+	match var.split_once('=') {
+		Some((name, _)) if looks_secret(name) => format!("{}=<redacted>", name),
+		_ => var.to_string()
+	}
+}
+
+/// Formats a command line for a report, quoting arguments with spaces and
+/// leaving out secrets: the values of "NAME=value" pairs with secret-looking
+/// names, and the arguments that follow secret-looking flags
+fn redact_command(args: &[String]) -> String {
+	let mut words = Vec::new();
+	let mut secrets = 0;
+
+	for arg in args {
+		if secrets > 0 {
+			words.push("<redacted>".to_string());
+			secrets -= 1;
+			continue;
+		}
+
+		let is_flag = (arg.starts_with('-') || arg.starts_with('+')) && !arg.contains('=');
+
+		if is_flag && (looks_secret(arg) || SECRET_SHORT_FLAGS.contains(&arg.as_str())) {
+			secrets = MULTI_VALUE_FLAGS.iter()
+				.find(|(flag, _)| arg.eq_ignore_ascii_case(flag))
+				.map_or(1, |(_, count)| *count);
+		}
+
+		let word = redact(arg);
+
+		if word.is_empty() || word.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+			words.push(format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\"")));
+		} else {
+			words.push(word);
+		}
+	}
+
+	words.join(" ")
+}
+
+/// Replaces the names of the accounts that logged in to Steam and the home
+/// directory in a log
+fn redact_log(text: &str, accounts: &[String], home: Option<&str>) -> String {
+	let mut text = match home {
+		Some(home) if !home.is_empty() => text.replace(home, "~"),
+		_ => text.to_string()
+	};
+
+	for account in accounts.iter().filter(|account| !account.is_empty()) {
+		text = text.replace(account.as_str(), "<account>");
+	}
+
+	text
+}
+
+fn run_command(args: &[&str]) -> String {
+	match Command::new(args[0]).args(&args[1..]).stderr(Stdio::null()).output() {
+		Ok(output) => String::from_utf8_lossy(&output.stdout).into_owned(),
+		Err(_) => "(not available)\n".to_string()
+	}
+}
+
+/// Writes a file of the report and notes what it holds in its contents
+fn write(dir: &Path, file: &str, text: &str, contents: &mut Vec<String>, description: &str) -> Result<(), String> {
+	fs::write(dir.join(file), text).map_err(|e| format!("Could not write {}: {}", file, e))?;
+	contents.push(format!("{}: {}", file, description));
+
+	Ok(())
+}
+
+fn session_report(id: u32) -> String {
+	let last = match LastSession::load().filter(|last| last.id == id) {
+		Some(last) => last,
+		None => return "No session of this game has been waited for yet; play it with '--wait' and create the report again to include its launch command and environment\n".to_string()
+	};
+
+	let mut text = format!("Game: {}\nStarted: {}\nRan for: {}\n",
+		last.name,
+		playtime::format_date(last.start),
+		session::format_duration(std::time::Duration::from_secs(last.seconds)));
+
+	if let Some(crash) = &last.crash {
+		text += &format!("Possible crash: {}\n", crash);
+	}
+
+	text += "\nCore dumps:\n";
+
+	if last.coredumps.is_empty() {
+		text += "none\n";
+	}
+
+	text += &last.coredumps.iter().map(|line| format!("{}\n", line)).collect::<String>();
+
+	text += "\nProcesses:\n";
+	text += &last.processes.iter()
+		.map(|process| format!("{}: {}\n", process.pid, redact_command(&process.args)))
+		.collect::<String>();
+
+	text
+}
+
+/// A bug report that was saved
+pub struct Report {
+	pub zip: PathBuf,
+	/// One line for each file in the zip, describing what it holds
+	pub contents: Vec<String>
+}
+
+/// Collects what is known about a game's last session, its launch options,
+/// the system and Steam's logs into a zip file in the current directory
+pub fn create(config: &Config, id: u32, name: &str, launch_options: Option<&str>) -> Result<Report, String> {
+	let slug: String = name.chars().map(|c| if c.is_alphanumeric() { c } else { '-' }).collect();
+	let report = format!("steamlet-bugreport-{}-{}", slug, playtime::now());
+	let dir = env::temp_dir().join(&report);
+	let logs = dir.join("logs");
+
+	fs::create_dir_all(&logs).map_err(|e| format!("Could not create {}: {}", logs.display(), e))?;
+
+	let mut contents = Vec::new();
+
+	write(&dir, "session.txt", &session_report(id), &mut contents,
+		"the last session's duration, core dumps and the command lines of its processes, with secrets redacted")?;
+
+	let environment: String = LastSession::load()
+		.filter(|last| last.id == id)
+		.map(|last| last.environment.iter().map(|var| format!("{}\n", redact(var))).collect())
+		.unwrap_or_default();
+
+	write(&dir, "environment.txt", &environment, &mut contents,
+		"the game's environment variables, with secret-looking values redacted")?;
+
+	let steam_options = localconfig::read_launch_options(config, id)
+		.unwrap_or_else(|e| Some(format!("unknown ({})", e)));
+
+	write(&dir, "launch-options.txt", &format!("steamlet: {}\nSteam: {}\n",
+		launch_options.unwrap_or("none"),
+		steam_options.as_deref().unwrap_or("none")), &mut contents,
+		"the game's launch options in steamlet and Steam")?;
+
+	let mut system = format!("steamlet {}\n\n", env!("CARGO_PKG_VERSION"));
+	system += &format!("$ cat /etc/os-release\n{}\n", fs::read_to_string("/etc/os-release").unwrap_or_default());
+
+	for args in SYSTEM_COMMANDS {
+		system += &format!("$ {}\n{}\n", args.join(" "), run_command(args));
+	}
+
+	write(&dir, "system.txt", &system, &mut contents,
+		"the steamlet version, OS release, kernel, CPU, memory and graphics")?;
+
+	if let Some((path, _)) = steam::find_manifest(config, id) {
+		let file = path.file_name().unwrap().to_string_lossy().into_owned();
+
+		if fs::copy(&path, dir.join(&file)).is_ok() {
+			contents.push(format!("{}: the game's install state, build and size from Steam", file));
+		}
+	}
+
+	// Proton writes its log to the home directory when PROTON_LOG=1 is set
+	let proton_log = dirs::home_dir().map(|home| home.join(format!("steam-{}.log", id)));
+	let steam_logs = steam::steam_root(config).map(|root| root.join("logs"));
+
+	let log_files = proton_log.into_iter()
+		.chain(STEAM_LOGS.iter().filter_map(|log| steam_logs.as_ref().map(|dir| dir.join(log))));
+
+	let accounts: Vec<String> = steam::login_users(config).into_iter()
+		.flat_map(|user| std::iter::once(user.account_name).chain(user.persona_name))
+		.collect();
+	let home = dirs::home_dir().map(|home| home.to_string_lossy().into_owned());
+
+	for log in log_files.filter(|log| log.is_file()) {
+		let file = log.file_name().unwrap().to_string_lossy().into_owned();
+		let text = match fs::read(&log) {
+			Ok(bytes) => redact_log(&String::from_utf8_lossy(&bytes), &accounts, home.as_deref()),
+			Err(_) => continue
+		};
+
+		write(&dir, &format!("logs/{}", file), &text, &mut contents,
+			"a log of Steam or Proton, with account names and the home directory replaced")?;
+	}
+
+	let zip = env::current_dir().map_err(|e| e.to_string())?.join(format!("{}.zip", report));
+
+	let zipped = Command::new("zip")
+		.args(["-r", "-q"])
+		.arg(&zip)
+		.arg(&report)
+		.current_dir(env::temp_dir())
+		.status()
+		.map(|status| status.success())
+		.unwrap_or(false);
+
+	if !zipped {
+		return Err(format!("Could not run 'zip'; the report's files are in {}", dir.display()));
+	}
+
+	fs::remove_dir_all(&dir).ok();
+
+	Ok(Report { zip, contents })
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn args(args: &[&str]) -> Vec<String> {
+		args.iter().map(|arg| arg.to_string()).collect()
+	}
+
+	#[test]
+	fn redacts_secret_variables() {
+		assert_eq!(redact("STEAM_API_KEY=abc123"), "STEAM_API_KEY=<redacted>");
+		assert_eq!(redact("github_token=abc=def"), "github_token=<redacted>");
+		assert_eq!(redact("--password=hunter2"), "--password=<redacted>");
+		assert_eq!(redact("SteamAppId=227300"), "SteamAppId=227300");
+		assert_eq!(redact("-novid"), "-novid");
+	}
+
+	#[test]
+	fn redacts_values_after_secret_flags() {
+		assert_eq!(redact_command(&args(&["game", "--token", "abc", "-p", "secret", "-windowed"])), "game --token <redacted> -p <redacted> -windowed");
+		assert_eq!(redact_command(&args(&["steam", "-login", "user", "hunter2", "-silent"])), "steam -login <redacted> <redacted> -silent");
+		assert_eq!(redact_command(&args(&["game", "--api-key=abc", "-port", "27015"])), "game --api-key=<redacted> -port 27015");
+	}
+
+	#[test]
+	fn quotes_arguments_with_spaces() {
+		assert_eq!(redact_command(&args(&["/games/My Game/game.exe", "+exec", "say \"hi\"", ""])), "\"/games/My Game/game.exe\" +exec \"say \\\"hi\\\"\" \"\"");
+		assert_eq!(redact_command(&args(&["game", "--secret", "two words"])), "game --secret <redacted>");
+	}
+
+	#[test]
+	fn redacts_accounts_and_home_in_logs() {
+		let log = "[2024-03-01] Logged in as gamer42 (Gamer)\nLoading /home/gamer42/.steam/steam/steamapps\n";
+		let accounts = args(&["gamer42", "Gamer", ""]);
+
+		assert_eq!(redact_log(log, &accounts, Some("/home/gamer42")), "[2024-03-01] Logged in as <account> (<account>)\nLoading ~/.steam/steam/steamapps\n");
+	}
+}
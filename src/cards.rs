@@ -0,0 +1,132 @@
+use crate::config::Config;
+use crate::output;
+use crate::steam;
+use crate::store;
+use crate::webapi;
+
+/// Progress of a game's trading card badge
+pub struct Badge {
+	pub level: u64,
+	pub xp: u64,
+	pub foil: bool
+}
+
+/// Fetches the user's badges for a game from the Web API, which requires
+/// the 'api_key' config value
+pub fn fetch_badges(config: &Config, id: u32) -> Option<Vec<Badge>> {
+	let steam_id = steam::current_user(config)?;
+	config.get_str("api_key")?;
+
+	let response = webapi::call(config, "IPlayerService/GetBadges/v1", &[("steamid", steam_id.to_string())])?;
+	let badges = response["badges"].as_array()?;
+
+	Some(badges.iter()
+		.filter(|badge| badge["appid"].as_u64() == Some(id as u64))
+		.map(|badge| Badge {
+			level: badge["level"].as_u64().unwrap_or(0),
+			xp: badge["xp"].as_u64().unwrap_or(0),
+			foil: badge["border_color"].as_u64().unwrap_or(0) == 1
+		})
+		.collect())
+}
+
+/// Fetches how many trading cards a game will still drop
+///
+/// This is only shown on the community badge page, so the value of the
+/// 'steamLoginSecure' browser cookie has to be set as 'steam_login_secure'
+pub fn fetch_drops_remaining(config: &Config, id: u32) -> Option<u32> {
+	let steam_id = steam::current_user(config)?;
+	let cookie = format!("Cookie: steamLoginSecure={}", config.get_str("steam_login_secure")?);
+	let url = format!("https://steamcommunity.com/profiles/{}/gamecards/{}/", steam_id, id);
+
+	let page = store::fetch(&url, &[&cookie])?;
+
+	parse_drops_remaining(&String::from_utf8_lossy(&page))
+}
+
+/// Reads the remaining card drops from a badge page, e.g. "3 card drops
+/// remaining" or "No card drops remaining"
+///
+/// The count is shown in the 'progress_info_bold' element of the drops
+/// section, whose wording depends on the page's language, so only its number
+/// is read; a count without a number means there are no drops left
+fn parse_drops_remaining(page: &str) -> Option<u32> {
+	let drops = &page[page.find("badge_title_stats_drops")?..];
+	let element = &drops[drops.find("progress_info_bold")?..];
+	let text = &element[element.find('>')? + 1..];
+	let text = &text[..text.find('<')?];
+
+	let count: String = text.chars()
+		.skip_while(|c| !c.is_ascii_digit())
+		.take_while(char::is_ascii_digit)
+		.collect();
+
+	if count.is_empty() {
+		Some(0)
+	} else {
+		count.parse().ok()
+	}
+}
+
+/// Prints the remaining card drops and badge progress of a game
+pub fn print_status(config: &Config, name: &str, id: u32, plain: bool) {
+	if steam::current_user(config).is_none() {
+		println!("Could not find your Steam ID; log in to Steam or set it with 'steamlet config steam_id <id>'");
+		return;
+	}
+
+	let mut fields = vec![("Game", name.to_string())];
+
+	let drops = match fetch_drops_remaining(config, id) {
+		Some(drops) => drops.to_string(),
+		None if config.get_str("steam_login_secure").is_none() => {
+			"unknown (set 'steam_login_secure' to your steamLoginSecure cookie)".to_string()
+		},
+		None => "unknown (could not read the badge page)".to_string()
+	};
+	fields.push(("Card drops remaining", drops));
+
+	match fetch_badges(config, id) {
+		Some(badges) if badges.is_empty() => fields.push(("Badge", "not crafted yet".to_string())),
+		Some(badges) => {
+			for badge in badges {
+				let kind = if badge.foil { "Foil badge" } else { "Badge" };
+				fields.push((kind, format!("level {} ({} XP)", badge.level, badge.xp)));
+			}
+		},
+		None if config.get_str("api_key").is_none() => {
+			fields.push(("Badge", "unknown (set 'api_key' to check badge progress)".to_string()));
+		},
+		None => fields.push(("Badge", "unknown (could not fetch badges)".to_string()))
+	}
+
+	output::print_fields(&fields, plain);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const PAGE: &str = r#"<div class="badge_title_stats">
+	<div class="badge_title_stats_playtime">&nbsp; 12.3 hrs on record </div>
+	<div class="badge_title_stats_drops">
+		<span class="progress_info_bold">3 card drops remaining</span>
+		<div style="clear: right"></div>
+		<div class="card_drop_info_dialog">Card drops are earned by playing</div>
+	</div>
+</div>"#;
+
+	#[test]
+	fn parses_drops_remaining() {
+		assert_eq!(parse_drops_remaining(PAGE), Some(3));
+		assert_eq!(parse_drops_remaining(&PAGE.replace("3 card drops", "No card drops")), Some(0));
+		assert_eq!(parse_drops_remaining(&PAGE.replace("3 card drops remaining", "Noch 2 Kartendrops")), Some(2));
+	}
+
+	#[test]
+	fn ignores_drops_outside_the_drops_section() {
+		let page = format!("<span class=\"progress_info_bold\">7 card drops</span>{}", PAGE);
+		assert_eq!(parse_drops_remaining(&page), Some(3));
+		assert_eq!(parse_drops_remaining("<span class=\"progress_info_bold\">7 card drops</span>"), None);
+	}
+}
@@ -0,0 +1,25 @@
+use std::process::{Command, Stdio};
+
+/// Commands that print the clipboard, tried in order
+static PASTE_COMMANDS: &[&[&str]] = &[
+	&["wl-paste", "--no-newline"],
+	&["xclip", "-selection", "clipboard", "-out"],
+	&["xsel", "--clipboard", "--output"],
+	&["pbpaste"]
+];
+
+/// Reads text from the system clipboard with the first paste command
+/// that works
+pub fn read() -> Option<String> {
+	PASTE_COMMANDS.iter().find_map(|command| {
+		let output = Command::new(command[0])
+			.args(&command[1..])
+			.stderr(Stdio::null())
+			.output()
+			.ok()
+			.filter(|output| output.status.success())?;
+
+		Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+			.filter(|text| !text.is_empty())
+	})
+}
@@ -0,0 +1,110 @@
+use std::fs;
+use std::path::PathBuf;
+use serde_json::{Map, Value};
+
+pub static CONFIG_FILE_NAME: &str = "config.json";
+
+/// User settings stored as a JSON object and addressed by dotted keys,
+/// e.g. `api_key` or `family.lenders`
+pub struct Config {
+	root: Value
+}
+
+impl Config {
+	pub fn path() -> PathBuf {
+		dirs::config_dir().unwrap().join("steamlet").join(CONFIG_FILE_NAME)
+	}
+
+	pub fn load() -> Config {
+		let root = fs::read_to_string(Config::path())
+			.ok()
+			.and_then(|text| serde_json::from_str(&text).ok())
+			.filter(Value::is_object)
+			.unwrap_or_else(|| Value::Object(Map::new()));
+
+		Config { root }
+	}
+
+	pub fn save(&self) -> Result<(), String> {
+		let path = Config::path();
+
+		fs::create_dir_all(path.parent().unwrap()).map_err(|e| e.to_string())?;
+		fs::write(&path, serde_json::to_string_pretty(&self.root).unwrap())
+			.map_err(|e| format!("Error while writing to {}: {}", CONFIG_FILE_NAME, e))
+	}
+
+	/// Gets the whole config as a JSON object
+	pub fn values(&self) -> &Value {
+		&self.root
+	}
+
+	pub fn get(&self, key: &str) -> Option<&Value> {
+		key.split('.').try_fold(&self.root, |node, part| node.get(part))
+	}
+
+	pub fn get_str(&self, key: &str) -> Option<&str> {
+		self.get(key).and_then(Value::as_str).filter(|s| !s.is_empty())
+	}
+
+	/// Gets a number, which may also have been set as a string
+	pub fn get_u64(&self, key: &str) -> Option<u64> {
+		match self.get(key)? {
+			Value::String(s) => s.parse().ok(),
+			value => value.as_u64()
+		}
+	}
+
+	/// Whether plain, screen-reader friendly output is always used
+	pub fn plain_output(&self) -> bool {
+		self.get("plain").and_then(Value::as_bool).unwrap_or(false)
+	}
+
+	/// Gets the global default of a command's flag from the 'defaults' section,
+	/// e.g. `defaults.play.wait`
+	pub fn default_flag(&self, command: &str, flag: &str) -> Option<&Value> {
+		self.get("defaults")?.get(command)?.get(flag)
+	}
+
+	pub fn default_bool(&self, command: &str, flag: &str) -> Option<bool> {
+		self.default_flag(command, flag).and_then(Value::as_bool)
+	}
+
+	pub fn default_str(&self, command: &str, flag: &str) -> Option<&str> {
+		self.default_flag(command, flag).and_then(Value::as_str)
+	}
+
+	/// Sets a key, creating any parent objects on the way
+	pub fn set(&mut self, key: &str, value: Value) {
+		let mut node = &mut self.root;
+
+		for part in key.split('.') {
+			if !node.is_object() {
+				*node = Value::Object(Map::new());
+			}
+
+			node = node.as_object_mut().unwrap()
+				.entry(part)
+				.or_insert(Value::Null);
+		}
+
+		*node = value;
+	}
+
+	/// Removes a key, returning whether it existed
+	pub fn unset(&mut self, key: &str) -> bool {
+		let (parent, last) = match key.rsplit_once('.') {
+			Some((parent, last)) => (parent.split('.').try_fold(&mut self.root, |node, part| node.get_mut(part)), last),
+			None => (Some(&mut self.root), key)
+		};
+
+		parent.and_then(Value::as_object_mut)
+			.and_then(|map| map.remove(last))
+			.is_some()
+	}
+}
+
+/// Parses a value given on the commandline; anything that isn't valid JSON is
+/// treated as a string
+pub fn parse_value(s: &str) -> Value {
+	serde_json::from_str(s).unwrap_or_else(|_| Value::String(s.to_string()))
+}
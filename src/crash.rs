@@ -0,0 +1,227 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::data;
+use crate::notify;
+use crate::playtime;
+use crate::session::{self, Session, SessionHook};
+
+static LAST_SESSION_FILE_NAME: &str = "last_session.json";
+
+/// Sessions shorter than this many seconds are reported as possible crashes,
+/// unless configured with 'crash.min_seconds'
+const DEFAULT_MIN_SECONDS: u64 = 60;
+
+/// What was seen of the last session that was waited for, kept for 'bugreport'
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LastSession {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub alias: Option<String>,
+	pub id: u32,
+	pub name: String,
+	/// When the game was launched, in seconds since the Unix epoch
+	pub start: u64,
+	pub seconds: u64,
+	/// The command lines of the game's processes
+	pub processes: Vec<Process>,
+	/// The environment of the game's main process
+	pub environment: Vec<String>,
+	/// Why the session looks like it crashed, if it does
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub crash: Option<String>,
+	/// Core dumps of the game's processes, as listed by coredumpctl
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub coredumps: Vec<String>
+}
+
+/// A process of a game and the arguments it was started with
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Process {
+	pub pid: u32,
+	pub args: Vec<String>
+}
+
+impl LastSession {
+	pub fn path() -> PathBuf {
+		data::data_dir().join(LAST_SESSION_FILE_NAME)
+	}
+
+	pub fn load() -> Option<LastSession> {
+		fs::read_to_string(LastSession::path())
+			.ok()
+			.and_then(|text| serde_json::from_str(&text).ok())
+	}
+
+	pub fn save(&self) {
+		if fs::write(LastSession::path(), serde_json::to_string_pretty(self).unwrap()).is_err() {
+			println!("Error while writing to {}", LAST_SESSION_FILE_NAME);
+		}
+	}
+}
+
+/// Reads a file of /proc/<pid> whose entries are separated by null bytes
+fn read_proc_list(pid: u32, file: &str) -> Vec<String> {
+	fs::read(format!("/proc/{}/{}", pid, file))
+		.map(|bytes| bytes.split(|b| *b == 0)
+			.filter(|part| !part.is_empty())
+			.map(|part| String::from_utf8_lossy(part).into_owned())
+			.collect())
+		.unwrap_or_default()
+}
+
+/// Lists the core dumps systemd-coredump recorded since a point in time for
+/// any of the given processes
+fn find_coredumps(since: u64, pids: &[u32]) -> Vec<String> {
+	let output = Command::new("coredumpctl")
+		.args(["list", "--json=short", "--no-pager", &format!("--since=@{}", since)])
+		.stderr(Stdio::null())
+		.output();
+
+	match output {
+		Ok(output) => parse_coredumps(&String::from_utf8_lossy(&output.stdout), pids),
+		Err(_) => Vec::new()
+	}
+}
+
+/// Picks the core dumps of the given processes from the JSON that
+/// 'coredumpctl list --json=short' prints, describing each in a line
+fn parse_coredumps(json: &str, pids: &[u32]) -> Vec<String> {
+	let entries: Vec<Value> = serde_json::from_str(json).unwrap_or_default();
+
+	entries.iter()
+		.filter(|entry| entry["pid"].as_u64().is_some_and(|pid| pids.iter().any(|p| u64::from(*p) == pid)))
+		.map(|entry| format!("{}: PID {} ({}) received signal {}; core file {}",
+			// Times are in microseconds
+			entry["time"].as_u64().map_or("unknown time".to_string(), |time| playtime::format_date(time / 1_000_000)),
+			entry["pid"],
+			entry["exe"].as_str().unwrap_or("unknown executable"),
+			entry["sig"],
+			entry["corefile"].as_str().unwrap_or("unknown")))
+		.collect()
+}
+
+/// Notices when a game exits suspiciously soon or leaves a core dump, and
+/// keeps what was seen of the session for 'bugreport'
+///
+/// The exit status of a game isn't available, since Steam starts it rather
+/// than steamlet
+pub struct CrashHook {
+	min_duration: Duration,
+	start: u64,
+	processes: Vec<Process>,
+	environment: Vec<String>
+}
+
+impl CrashHook {
+	pub fn new(config: &Config) -> CrashHook {
+		let min_seconds = config.get_u64("crash.min_seconds").unwrap_or(DEFAULT_MIN_SECONDS);
+
+		CrashHook {
+			min_duration: Duration::from_secs(min_seconds),
+			start: playtime::now(),
+			processes: Vec::new(),
+			environment: Vec::new()
+		}
+	}
+}
+
+impl SessionHook for CrashHook {
+	fn on_launch(&mut self, _session: &Session) {
+		self.start = playtime::now();
+	}
+
+	fn on_tick(&mut self, session: &Session) -> bool {
+		// The session has just looked for the game's processes
+		for pid in &session.pids {
+			if self.processes.iter().any(|process| process.pid == *pid) {
+				continue;
+			}
+
+			let pid = *pid;
+			self.processes.push(Process { pid, args: read_proc_list(pid, "cmdline") });
+
+			// The first process with the app's ID in its environment is the
+			// game itself rather than Steam's wrapper
+			let environment = read_proc_list(pid, "environ");
+
+			if self.environment.is_empty() && environment.iter().any(|var| var.starts_with("SteamAppId=")) {
+				self.environment = environment;
+			}
+		}
+
+		true
+	}
+
+	fn on_exit(&mut self, session: &Session) {
+		let played = session.played();
+
+		// The game never started, or it's still running
+		if self.processes.is_empty() || session.interrupted {
+			return;
+		}
+
+		let pids: Vec<u32> = self.processes.iter().map(|process| process.pid).collect();
+		let coredumps = find_coredumps(self.start, &pids);
+
+		let crash = if !coredumps.is_empty() {
+			Some(format!("{} left a core dump", session.name))
+		} else if played < self.min_duration && !session.closed {
+			Some(format!("{} exited after only {}", session.name, session::format_duration(played)))
+		} else {
+			None
+		};
+
+		if let Some(crash) = &crash {
+			let report = match &session.alias {
+				Some(alias) => format!("steamlet bugreport {}", alias),
+				None => format!("steamlet bugreport -i {}", session.id)
+			};
+
+			println!("{}; it may have crashed. Collect logs for a bug report with '{}'", crash, report);
+			notify::desktop("Game may have crashed", crash);
+		}
+
+		LastSession {
+			alias: session.alias.clone(),
+			id: session.id,
+			name: session.name.clone(),
+			start: self.start,
+			seconds: played.as_secs(),
+			processes: std::mem::take(&mut self.processes),
+			environment: std::mem::take(&mut self.environment),
+			crash,
+			coredumps
+		}.save();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn picks_coredumps_by_pid() {
+		// uid, gid and sig equal the PIDs of other coredumps, which must not match
+		let json = r#"[
+			{"time":1709312645000000,"pid":4242,"uid":1000,"gid":1000,"sig":11,"corefile":"present","exe":"/games/ets2/bin/eurotrucks2","size":1048576},
+			{"time":1709312700000000,"pid":1000,"uid":4242,"gid":4242,"sig":6,"corefile":"missing","exe":"/usr/bin/other","size":null},
+			{"time":1709312800000000,"pid":11,"uid":1000,"gid":1000,"sig":4242,"corefile":"present","exe":"/usr/bin/another"}
+		]"#;
+
+		let coredumps = parse_coredumps(json, &[4242]);
+
+		assert_eq!(coredumps.len(), 1);
+		assert!(coredumps[0].ends_with(": PID 4242 (/games/ets2/bin/eurotrucks2) received signal 11; core file present"), "{}", coredumps[0]);
+	}
+
+	#[test]
+	fn ignores_output_other_than_json() {
+		assert!(parse_coredumps("No coredumps found.", &[4242]).is_empty());
+		assert!(parse_coredumps("", &[4242]).is_empty());
+	}
+}
@@ -0,0 +1,211 @@
+use std::path::PathBuf;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::{BufReader, BufWriter, SeekFrom};
+use std::io::prelude::*;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+use crate::breaks::Breaks;
+use crate::limits::Limits;
+use crate::store::AppType;
+
+pub static DATA_FILE_NAME: &str = "steamlet.json";
+
+/// A saved alias and everything known about its Steam app
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Alias {
+	pub id: u32,
+
+	#[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+	pub app_type: Option<AppType>,
+
+	/// A demo or playtest app ID of the same game
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub demo: Option<u32>,
+
+	/// The name of the game in the store
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub name: Option<String>,
+
+	/// Set for dormant aliases, e.g. imported from the wishlist, whose game
+	/// hasn't been bought yet
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub not_owned: bool,
+
+	/// Set once neither Steam's app info cache nor the store knew the type of
+	/// the app, so that it isn't looked up again
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub unknown_type: bool,
+
+	#[serde(default, skip_serializing_if = "PlayDefaults::is_empty")]
+	pub defaults: PlayDefaults,
+
+	#[serde(default, skip_serializing_if = "Limits::is_empty")]
+	pub limits: Limits,
+
+	#[serde(default, skip_serializing_if = "Breaks::is_empty")]
+	pub breaks: Breaks,
+
+	/// Launch options kept in sync with the Steam client through 'opts'
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub launch_options: Option<String>
+}
+
+/// Default 'play' flags of an alias; unset flags fall back to the global default
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PlayDefaults {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub wait: Option<bool>,
+
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub big_picture: Option<bool>,
+
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub dnd: Option<bool>,
+
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub power_profile: Option<String>,
+
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub governor: Option<String>,
+
+	/// Launch options passed along when starting the game, on top of the
+	/// ones set in Steam
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub launch_options: Option<String>
+}
+
+impl PlayDefaults {
+	pub fn is_empty(&self) -> bool {
+		*self == PlayDefaults::default()
+	}
+
+	/// Labeled values for printing with 'output::print_fields'
+	pub fn fields(&self) -> Vec<(&'static str, String)> {
+		let flag = |value: Option<bool>| value.map_or("unset".to_string(), |b| b.to_string());
+		let setting = |value: &Option<String>| value.clone().unwrap_or_else(|| "unset".to_string());
+
+		vec![
+			("Wait", flag(self.wait)),
+			("Big Picture", flag(self.big_picture)),
+			("Do not disturb", flag(self.dnd)),
+			("Power profile", setting(&self.power_profile)),
+			("CPU governor", setting(&self.governor)),
+			("Launch options", setting(&self.launch_options))
+		]
+	}
+}
+
+impl Alias {
+	pub fn new(id: u32) -> Alias {
+		Alias {
+			id,
+			app_type: None,
+			demo: None,
+			name: None,
+			not_owned: false,
+			unknown_type: false,
+			defaults: PlayDefaults::default(),
+			limits: Limits::default(),
+			breaks: Breaks::default(),
+			launch_options: None
+		}
+	}
+}
+
+/// Turns a game name into an alias, e.g. "Euro Truck Simulator® 2" into
+/// "euro truck simulator 2"
+pub fn alias_from_name(name: &str) -> String {
+	let cleaned: String = name.chars()
+		.filter(|c| !matches!(c, '™' | '®' | '©'))
+		.collect();
+
+	cleaned.split_whitespace()
+		.collect::<Vec<_>>()
+		.join(" ")
+		.to_lowercase()
+}
+
+/// The alias data file and its parsed contents
+pub struct AliasData {
+	file: File,
+	pub aliases: HashMap<String, Alias>
+}
+
+/// Gets the local data directory used by steamlet
+pub fn data_dir() -> PathBuf {
+	dirs::data_local_dir().unwrap().join("steamlet")
+}
+
+impl AliasData {
+	pub fn path() -> PathBuf {
+		data_dir().join(DATA_FILE_NAME)
+	}
+
+	pub fn load() -> AliasData {
+		let data_dir: PathBuf = data_dir();
+		let file: File;
+		let mut aliases: HashMap<String, Alias> = HashMap::new();
+
+		// Create a new file if the local data directory does not exist
+		if !data_dir.exists() {
+			std::fs::create_dir_all(data_dir.as_path()).unwrap();
+
+			file = OpenOptions::new()
+				.write(true)
+				.create_new(true)
+				.open(AliasData::path().as_path())
+				.unwrap();
+		} else {
+			file = OpenOptions::new()
+				.read(true)
+				.write(true)
+				.open(AliasData::path().as_path())
+				.unwrap();
+
+			let buf_reader = BufReader::new(&file);
+			// Read file contents into HashMap
+			let raw: HashMap<String, Value> = serde_json::from_reader(buf_reader)
+				.unwrap_or_default();
+
+			for (key, value) in raw {
+				// Older data files map each alias directly to its ID
+				let alias = match value.as_u64() {
+					Some(id) => Some(Alias::new(id as u32)),
+					None => serde_json::from_value(value).ok()
+				};
+
+				match alias {
+					Some(alias) => { aliases.insert(key, alias); },
+					None => println!("Skipping malformed alias '{}' in {}", key, DATA_FILE_NAME)
+				}
+			}
+		}
+
+		AliasData { file, aliases }
+	}
+
+	pub fn save(&self, message: String) {
+		// Create BufWriter for the file
+		let mut buf_writer = BufWriter::new(&self.file);
+
+		// Clear the file contents and set the cursor to position '0'
+		self.file.set_len(0).unwrap();
+		buf_writer.seek(SeekFrom::Start(0)).unwrap();
+
+		// Write data to the file
+		match serde_json::to_writer_pretty(&mut buf_writer, &self.aliases) {
+			Ok(_) => {
+				buf_writer.flush().unwrap();
+				if !message.is_empty() {
+					println!("{}", message);
+				}
+			},
+			Err(_) => {
+				println!("Error while writing to {}", DATA_FILE_NAME);
+			}
+		}
+	}
+}
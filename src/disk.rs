@@ -0,0 +1,58 @@
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+
+use crate::config::Config;
+
+/// Free space a Steam library should have left, unless configured
+const DEFAULT_MIN_FREE_GB: u64 = 5;
+
+const GB: u64 = 1024 * 1024 * 1024;
+
+/// Gets the free space of the partition a path is on, in bytes
+pub fn free_space(path: &Path) -> Option<u64> {
+	let path = CString::new(path.as_os_str().as_bytes()).ok()?;
+	let mut stats: libc::statvfs = unsafe { std::mem::zeroed() };
+
+	if unsafe { libc::statvfs(path.as_ptr(), &mut stats) } != 0 {
+		return None;
+	}
+
+	// The space available to unprivileged users, like 'df' shows
+	Some(stats.f_bavail as u64 * stats.f_frsize as u64)
+}
+
+/// Checks that the Steam library with the most free space of the given ones
+/// has at least 'disk.min_free_gb' GB left, warning if it hasn't
+///
+/// Returns false if the action should be aborted, which only happens if the
+/// 'disk.abort' config key is true
+pub fn check(config: &Config, libraries: &[PathBuf], action: &str) -> bool {
+	let min_free = config.get_u64("disk.min_free_gb").unwrap_or(DEFAULT_MIN_FREE_GB);
+
+	if min_free == 0 {
+		return true;
+	}
+
+	let most_free = libraries.iter()
+		.filter_map(|dir| free_space(dir).map(|free| (dir, free)))
+		.max_by_key(|(_, free)| *free);
+
+	let (dir, free) = match most_free {
+		Some(most_free) => most_free,
+		None => return true
+	};
+
+	if free >= min_free.saturating_mul(GB) {
+		return true;
+	}
+
+	println!("Warning: only {:.1} GB is free on the Steam library at {} (less than {} GB)", free as f64 / GB as f64, dir.display(), min_free);
+
+	if config.get("disk.abort").and_then(|v| v.as_bool()).unwrap_or(false) {
+		println!("Not {}; free up some space, or lower the 'disk.min_free_gb' config key", action);
+		return false;
+	}
+
+	true
+}
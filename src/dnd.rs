@@ -0,0 +1,119 @@
+use std::env;
+use std::process::{Command, Stdio};
+
+use crate::session::{Session, SessionHook};
+
+/// A way of turning do-not-disturb on and off, by reading and writing a
+/// setting through the given commands
+struct Backend {
+	name: &'static str,
+	/// Only used if the current desktop contains this name
+	desktop: Option<&'static str>,
+	/// Prints the current value of the setting
+	read: &'static [&'static str],
+	/// Sets the setting to the value given as the last argument
+	write: &'static [&'static str],
+	/// The value that turns do-not-disturb on
+	on: &'static str
+}
+
+const BACKENDS: &[Backend] = &[
+	Backend {
+		name: "dunst",
+		desktop: None,
+		read: &["dunstctl", "is-paused"],
+		write: &["dunstctl", "set-paused"],
+		on: "true"
+	},
+	Backend {
+		name: "GNOME",
+		desktop: Some("GNOME"),
+		read: &["gsettings", "get", "org.gnome.desktop.notifications", "show-banners"],
+		write: &["gsettings", "set", "org.gnome.desktop.notifications", "show-banners"],
+		on: "false"
+	},
+	// Plasma is in do-not-disturb mode until the time in this setting
+	Backend {
+		name: "KDE Plasma",
+		desktop: Some("KDE"),
+		read: &["kreadconfig6", "--file", "plasmanotifyrc", "--group", "DoNotDisturb", "--key", "Until"],
+		write: &["kwriteconfig6", "--notify", "--file", "plasmanotifyrc", "--group", "DoNotDisturb", "--key", "Until"],
+		on: "2999,12,31,0,0,0"
+	},
+	Backend {
+		name: "KDE Plasma",
+		desktop: Some("KDE"),
+		read: &["kreadconfig5", "--file", "plasmanotifyrc", "--group", "DoNotDisturb", "--key", "Until"],
+		write: &["kwriteconfig5", "--notify", "--file", "plasmanotifyrc", "--group", "DoNotDisturb", "--key", "Until"],
+		on: "2999,12,31,0,0,0"
+	}
+];
+
+impl Backend {
+	fn read(&self) -> Option<String> {
+		let output = Command::new(self.read[0])
+			.args(&self.read[1..])
+			.stderr(Stdio::null())
+			.output()
+			.ok()
+			.filter(|output| output.status.success())?;
+
+		Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+	}
+
+	fn write(&self, value: &str) -> bool {
+		Command::new(self.write[0])
+			.args(&self.write[1..])
+			.arg(value)
+			.stderr(Stdio::null())
+			.status()
+			.map(|status| status.success())
+			.unwrap_or(false)
+	}
+}
+
+/// Finds the do-not-disturb backend of the running desktop along with the
+/// current value of its setting
+fn find_backend() -> Option<(&'static Backend, String)> {
+	let desktop = env::var("XDG_CURRENT_DESKTOP").unwrap_or_default().to_uppercase();
+
+	BACKENDS.iter()
+		.filter(|backend| backend.desktop.is_none_or(|name| desktop.contains(name)))
+		.find_map(|backend| backend.read().map(|value| (backend, value)))
+}
+
+/// Turns on the desktop's do-not-disturb mode while a game runs and restores
+/// the previous setting afterwards
+#[derive(Default)]
+pub struct DndHook {
+	changed: Option<(&'static Backend, String)>
+}
+
+impl SessionHook for DndHook {
+	fn on_launch(&mut self, _session: &Session) {
+		match find_backend() {
+			Some((backend, previous)) if previous == backend.on => {
+				println!("Do-not-disturb is already on ({})", backend.name);
+			},
+			Some((backend, previous)) => {
+				if backend.write(backend.on) {
+					println!("Turned on do-not-disturb ({})", backend.name);
+					self.changed = Some((backend, previous));
+				} else {
+					println!("Could not turn on do-not-disturb ({})", backend.name);
+				}
+			},
+			None => println!("Could not find a supported notification daemon for do-not-disturb (GNOME, KDE Plasma or dunst)")
+		}
+	}
+
+	fn on_exit(&mut self, _session: &Session) {
+		if let Some((backend, previous)) = self.changed.take() {
+			if backend.write(&previous) {
+				println!("Restored do-not-disturb ({})", backend.name);
+			} else {
+				println!("Could not restore do-not-disturb ({})", backend.name);
+			}
+		}
+	}
+}
@@ -0,0 +1,43 @@
+use crate::config::Config;
+use crate::steam;
+use steamlet::vdf::Vdf;
+use crate::webapi;
+
+/// Gets the Steam ID of the account that lends an installed app to the
+/// current user through Family Sharing, if any
+pub fn find_lender(config: &Config, id: u32) -> Option<u64> {
+	let user = steam::current_user(config)?;
+	let (_, manifest) = steam::find_manifest(config, id)?;
+	let owner: u64 = manifest.get_path(&["AppState", "LastOwner"])
+		.and_then(Vdf::as_str)?
+		.parse()
+		.ok()?;
+
+	if owner != 0 && owner != user {
+		Some(owner)
+	} else {
+		None
+	}
+}
+
+/// Warns before launching a Family Shared app whose lender is currently
+/// playing, since the lender takes priority and the session may be cut short
+pub fn warn_if_lender_playing(config: &Config, id: u32) {
+	let lender = match find_lender(config, id) {
+		Some(lender) => lender,
+		None => return
+	};
+
+	if config.get_str("api_key").is_none() {
+		println!("Note: this game is Family Shared by {}; set 'api_key' with 'steamlet config' to check whether they are playing", lender);
+		return;
+	}
+
+	match webapi::player_summary(config, lender) {
+		Some(webapi::PlayerSummary { name, playing: Some(game) }) => {
+			println!("Warning: this game is Family Shared by {}, who is currently playing {}; your session may be interrupted", name, game);
+		},
+		Some(_) => {},
+		None => println!("Could not check whether the Family Sharing lender {} is playing", lender)
+	}
+}
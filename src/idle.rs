@@ -0,0 +1,103 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::session::{self, InterruptGuard, Session, POLL_INTERVAL, START_TIMEOUT};
+use crate::steam::open_steam_url;
+
+/// The most games Steam lets a user be in-game in at once
+pub const MAX_CONCURRENT_GAMES: usize = 32;
+
+/// Runs games at the same time for a while, e.g. to collect trading card drops,
+/// in batches of at most 'max' games
+///
+/// Ctrl-C (or a hangup or SIGTERM) closes the games of the current batch and
+/// stops idling
+pub fn idle(games: &[(String, u32)], max: usize, duration: Duration) {
+	let guard = InterruptGuard::new();
+	let max = max.clamp(1, MAX_CONCURRENT_GAMES);
+	let batches: Vec<_> = games.chunks(max).collect();
+
+	for (i, batch) in batches.iter().enumerate() {
+		if batches.len() > 1 {
+			println!("Batch {} of {}", i + 1, batches.len());
+		}
+
+		let mut sessions: Vec<Session> = batch.iter()
+			.map(|(alias, id)| {
+				let session = Session::new(*id, format!("{} ({})", alias, id), Some(alias.clone()));
+				println!("Starting {}", session.name);
+				open_steam_url(format!("steam://run/{}", id));
+				session
+			})
+			.collect();
+
+		// Wait for all of the batch's games together, rather than one after another
+		let launched = Instant::now();
+
+		while launched.elapsed() <= START_TIMEOUT {
+			for session in sessions.iter_mut() {
+				session.refresh();
+			}
+
+			if sessions.iter().all(Session::has_started) || guard.interrupted() {
+				break;
+			}
+
+			thread::sleep(POLL_INTERVAL);
+		}
+
+		if guard.interrupted() {
+			return stop(&mut sessions);
+		}
+
+		sessions.retain(|session| {
+			if !session.has_started() {
+				println!("{} did not start; skipping", session.name);
+			}
+
+			session.has_started()
+		});
+
+		if sessions.is_empty() {
+			continue;
+		}
+
+		println!("Idling {} games for {}...", sessions.len(), session::format_duration(duration));
+		let started = Instant::now();
+
+		// Stop early if every game has been closed already
+		while started.elapsed() < duration {
+			for session in sessions.iter_mut() {
+				session.refresh();
+			}
+
+			if sessions.iter().all(|session| session.running.is_empty()) || guard.interrupted() {
+				break;
+			}
+
+			thread::sleep(POLL_INTERVAL.min(duration.saturating_sub(started.elapsed())));
+		}
+
+		if guard.interrupted() {
+			return stop(&mut sessions);
+		}
+
+		close(&mut sessions);
+	}
+
+	println!("Finished idling");
+}
+
+fn close(sessions: &mut [Session]) {
+	for session in sessions.iter_mut() {
+		println!("Closing {}", session.name);
+		session::terminate(session);
+	}
+}
+
+/// Closes the games of an interrupted batch, including those that only
+/// started while it was waited for
+fn stop(sessions: &mut [Session]) {
+	println!("Stopped idling");
+	close(sessions);
+}
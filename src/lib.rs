@@ -0,0 +1,6 @@
+//! Parts of steamlet that are useful on their own, such as the parsers for
+//! Steam's text VDF (KeyValues) files like appmanifests and libraryfolders.vdf,
+//! and for its binary VDF files like appinfo.vdf and shortcuts.vdf
+
+pub mod binary_vdf;
+pub mod vdf;
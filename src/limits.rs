@@ -0,0 +1,199 @@
+use std::time::Duration;
+use serde::{Serialize, Deserialize};
+
+use crate::config::Config;
+use crate::notify;
+use crate::playtime::{self, Playtime};
+use crate::session::{self, Session, SessionHook};
+
+/// Minutes left at which a warning is shown
+static WARNINGS: &[u64] = &[15, 5, 1];
+
+/// Daily and weekly play-time limits in minutes
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Limits {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub daily: Option<u64>,
+
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub weekly: Option<u64>,
+
+	/// Whether the game is closed once a limit is exceeded
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub enforce: Option<bool>
+}
+
+impl Limits {
+	pub fn is_empty(&self) -> bool {
+		*self == Limits::default()
+	}
+
+	/// Reads the global limits on all games from the 'limits' section of the config
+	pub fn global(config: &Config) -> Limits {
+		Limits {
+			daily: config.get_u64("limits.daily"),
+			weekly: config.get_u64("limits.weekly"),
+			enforce: config.get("limits.enforce").and_then(|v| v.as_bool())
+		}
+	}
+
+	/// Labeled values for printing with 'output::print_fields'
+	pub fn fields(&self) -> Vec<(&'static str, String)> {
+		let minutes = |limit: Option<u64>| limit.map_or("unset".to_string(), |m| format!("{}m", m));
+
+		vec![
+			("Daily", minutes(self.daily)),
+			("Weekly", minutes(self.weekly)),
+			("Enforce", self.enforce.unwrap_or(false).to_string())
+		]
+	}
+}
+
+/// A limit that applies to a session, and how much of its period had been
+/// played before the session
+struct Remaining {
+	label: String,
+	alias: Option<String>,
+	limit: Duration,
+	/// How long the limit's period is, a day or a week
+	period: u64,
+	/// When the current period started, in seconds since the Unix epoch
+	since: u64,
+	played: Duration
+}
+
+impl Remaining {
+	/// How much of the limit is left, with a session that started at a point
+	/// in time counted only from the start of the period
+	fn left(&mut self, session_start: u64) -> Duration {
+		let now = playtime::now();
+
+		// The session went past midnight or into a new week
+		if now >= self.since + self.period {
+			self.since += (now - self.since) / self.period * self.period;
+			self.played = Playtime::load().played_since(self.since, self.alias.as_deref());
+		}
+
+		let session = Duration::from_secs(now.saturating_sub(session_start.max(self.since)));
+
+		self.limit.saturating_sub(self.played + session)
+	}
+}
+
+/// Warns as play-time limits approach and closes the game once one is
+/// exceeded if it is enforced
+pub struct LimitHook {
+	remaining: Vec<Remaining>,
+	enforce: bool,
+	warned: Vec<u64>,
+	warned_exceeded: bool
+}
+
+impl LimitHook {
+	/// Builds the hook from an alias' limits and the global ones, or returns
+	/// None if no limits apply
+	pub fn new(alias: Option<&str>, limits: &Limits, global: &Limits) -> Option<LimitHook> {
+		let playtime = Playtime::load();
+		let day = (playtime::start_of_day(), playtime::DAY);
+		let week = (playtime::start_of_week(), 7 * playtime::DAY);
+		let mut remaining = Vec::new();
+
+		let mut add = |label: String, limit: Option<u64>, (since, period): (u64, u64), alias: Option<&str>| {
+			if let Some(minutes) = limit {
+				remaining.push(Remaining {
+					label,
+					alias: alias.map(String::from),
+					limit: Duration::from_secs(minutes * 60),
+					period,
+					since,
+					played: playtime.played_since(since, alias)
+				});
+			}
+		};
+
+		if let Some(alias) = alias {
+			add(format!("daily limit of '{}'", alias), limits.daily, day, Some(alias));
+			add(format!("weekly limit of '{}'", alias), limits.weekly, week, Some(alias));
+		}
+
+		add("daily limit".to_string(), global.daily, day, None);
+		add("weekly limit".to_string(), global.weekly, week, None);
+
+		if remaining.is_empty() {
+			return None;
+		}
+
+		Some(LimitHook {
+			remaining,
+			enforce: limits.enforce.or(global.enforce).unwrap_or(false),
+			warned: Vec::new(),
+			warned_exceeded: false
+		})
+	}
+
+	pub fn enforced(&self) -> bool {
+		self.enforce
+	}
+
+	/// The limit that runs out first and how much of it is left, for a
+	/// session that has been played for a while
+	fn closest(&mut self, played: Duration) -> (String, Duration) {
+		let session_start = playtime::now().saturating_sub(played.as_secs());
+		let closest = self.remaining.iter_mut()
+			.map(|r| (r.left(session_start), r))
+			.min_by_key(|(left, _)| *left)
+			.unwrap();
+
+		(closest.1.label.clone(), closest.0)
+	}
+}
+
+fn alert(message: &str) {
+	println!("{}", message);
+	notify::desktop("Play-time limit", message);
+}
+
+impl SessionHook for LimitHook {
+	fn on_launch(&mut self, _session: &Session) {
+		let (label, left) = self.closest(Duration::from_secs(0));
+
+		if left.as_secs() == 0 {
+			println!("Warning: the {} has already been reached", label);
+		} else {
+			println!("{} left until the {}", session::format_duration(left), label);
+		}
+	}
+
+	fn on_tick(&mut self, session: &Session) -> bool {
+		let (label, left) = self.closest(session.played());
+		let minutes_left = left.as_secs().div_ceil(60);
+
+		if left.as_secs() == 0 {
+			if self.enforce {
+				alert(&format!("The {} has been reached; closing {}", label, session.name));
+				return false;
+			}
+
+			if !self.warned_exceeded {
+				alert(&format!("The {} has been reached", label));
+				self.warned_exceeded = true;
+			}
+		} else if WARNINGS.iter().any(|w| minutes_left <= *w && !self.warned.contains(w)) {
+			let action = if self.enforce { "is closed" } else { "reaches its limit" };
+			alert(&format!("{} left until {} {} ({})", session::format_duration(left), session.name, action, label));
+
+			// Skip the larger warnings as well when starting close to the limit
+			self.warned.extend(WARNINGS.iter().filter(|w| **w >= minutes_left));
+		}
+
+		true
+	}
+
+	fn allows_interrupt(&mut self, session: &Session) -> bool {
+		if self.enforce {
+			println!("Play-time limits are enforced for {}, so it is closed rather than left running", session.name);
+		}
+
+		!self.enforce
+	}
+}
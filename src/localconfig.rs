@@ -0,0 +1,76 @@
+use std::fs;
+use std::path::PathBuf;
+
+use steamlet::vdf::{self, Vdf};
+
+use crate::config::Config;
+use crate::playtime;
+use crate::steam;
+
+/// Where the settings of an app are kept in localconfig.vdf
+const APPS_PATH: &[&str] = &["UserLocalConfigStore", "Software", "Valve", "Steam", "apps"];
+
+/// Gets the path of the current user's localconfig.vdf, where the Steam
+/// client keeps per-user app settings such as launch options
+pub fn path(config: &Config) -> Result<PathBuf, String> {
+	let dir = steam::userdata_dir(config)
+		.ok_or("Could not find your Steam userdata directory; log in to Steam or set 'steam_id' with 'steamlet config'")?;
+
+	Ok(dir.join("config/localconfig.vdf"))
+}
+
+/// Reads the launch options that are set for an app in the Steam client
+pub fn read_launch_options(config: &Config, id: u32) -> Result<Option<String>, String> {
+	let localconfig = vdf::parse_file(&path(config)?)?;
+
+	let options = localconfig.get_path(APPS_PATH)
+		.and_then(|apps| apps.get(&id.to_string()))
+		.and_then(|app| app.get("LaunchOptions"))
+		.and_then(Vdf::as_str)
+		.filter(|options| !options.is_empty())
+		.map(String::from);
+
+	Ok(options)
+}
+
+/// Sets (or removes, with None) the launch options of an app in the Steam
+/// client after backing up localconfig.vdf, returning the backup's path
+///
+/// The file is left alone if it holds anything that rewriting it would lose,
+/// such as comments
+pub fn write_launch_options(config: &Config, id: u32, options: Option<&str>) -> Result<PathBuf, String> {
+	let path = path(config)?;
+	let text = fs::read_to_string(&path)
+		.map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+	let mut localconfig = vdf::parse_exact(&text)
+		.map_err(|e| format!("Not changing {}, since it can't be rewritten as it is: {}", path.display(), e))?;
+
+	let app = APPS_PATH.iter()
+		.chain(&[id.to_string().as_str()])
+		.try_fold(&mut localconfig, |node, key| node.entry(key))
+		.ok_or_else(|| format!("Unexpected structure of {}", path.display()))?;
+
+	match options {
+		Some(options) => app.insert("LaunchOptions", Vdf::Str(options.to_string())),
+		None => {
+			app.remove("LaunchOptions");
+		}
+	}
+
+	// Backups made within the same second get a counter instead of replacing each other
+	let now = playtime::now();
+	let backup = (0..)
+		.map(|n| match n {
+			0 => path.with_file_name(format!("localconfig.vdf.{}.bak", now)),
+			n => path.with_file_name(format!("localconfig.vdf.{}-{}.bak", now, n))
+		})
+		.find(|backup| !backup.exists())
+		.unwrap();
+
+	fs::copy(&path, &backup)
+		.map_err(|e| format!("Could not back up {}: {}", path.display(), e))?;
+
+	vdf::write_file(&path, &localconfig)?;
+
+	Ok(backup)
+}
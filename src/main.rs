@@ -1,36 +1,84 @@
 use structopt::StructOpt;
+use structopt::clap::{App, AppSettings, ErrorKind};
 use std::process::Command;
-use std::path::PathBuf;
-use std::fs::File;
-use std::fs::OpenOptions;
-use std::io::{BufReader, BufWriter, SeekFrom};
-use std::io::prelude::*;
-use std::collections::HashMap;
+use std::io::Write;
+use serde_json::Value;
 
 extern crate dirs;
 
+mod breaks;
+mod bugreport;
+mod cards;
+mod clipboard;
+mod config;
+mod crash;
+mod data;
+mod disk;
+mod dnd;
+mod family;
+mod idle;
+mod limits;
+mod localconfig;
+mod manifest;
+mod notify;
+mod output;
+mod playtime;
+mod power;
+mod queue;
+mod session;
+mod shell;
+mod steam;
+mod store;
+mod top;
+mod watch;
+mod webapi;
+mod wishlist;
+
+use config::Config;
+use data::{Alias, AliasData, PlayDefaults};
+use breaks::{BreakHook, Breaks};
+use limits::{LimitHook, Limits};
+use queue::Queue;
+use session::{Session, SessionHook};
+use steam::open_steam_url;
+use store::{AppLink, AppType};
+
 #[derive(StructOpt, Debug)]
 enum SteamletCommand {
 	/// Plays a Steam game via an alias or by a Steam game ID (with -i)
-	Play {
-		/// Flag to use a game ID instead of an alias
-		#[structopt(short = "i", long = "id")]
-		use_id: bool,
+	Play(PlayArgs),
 
-		/// The input for selecting the game (an alias or an ID with the '-i' flag)
-		#[structopt(name = "game")]
-		game_str: String,
+	/// Sets the default 'play' flags of an alias (shows them without any flags)
+	Defaults {
+		/// The alias whose defaults are set
+		alias: String,
+
+		#[structopt(flatten)]
+		flags: PlayFlags,
+
+		/// Flag to remove all defaults of the alias
+		#[structopt(short = "c", long = "clear")]
+		clear: bool
 	},
 
 	/// Adds or sets an alias to an associated Steam game ID (alt. command 'add')
 	#[structopt(alias = "add")]
 	Set {
-		/// The alias to be made
-		alias: String,
+		/// The alias to be made, or just a store URL to name the alias after the game
+		#[structopt(required_unless = "from-clipboard")]
+		alias: Option<String>,
 
-		/// The Steam game ID to be associated with
-		#[structopt(name = "steam_id")]
-		id: u32
+		/// The Steam game ID or store URL to be associated with
+		#[structopt(name = "steam_id", conflicts_with = "from-clipboard")]
+		target: Option<String>,
+
+		/// Flag to take the Steam game ID or store URL from the clipboard
+		#[structopt(short = "c", long = "from-clipboard")]
+		from_clipboard: bool,
+
+		/// A demo or playtest app ID to attach to the alias
+		#[structopt(short = "d", long = "demo", name = "demo_id")]
+		demo: Option<u32>
 	},
 
 	/// Removes an alias (alt. command 'rm')
@@ -38,13 +86,408 @@ enum SteamletCommand {
 	Remove {
 		/// List of one or more aliases to be removed
 		#[structopt(required = true, min_values = 1)]
-		aliases: Vec<String>
+		aliases: Vec<String>,
+
+		/// Flag to ask for confirmation before removing
+		#[structopt(short = "c", long = "confirm", overrides_with = "no-confirm")]
+		confirm: bool,
+
+		/// Flag to remove without asking for confirmation
+		#[structopt(long = "no-confirm", overrides_with = "confirm")]
+		no_confirm: bool
 	},
 
 	/// Lists all aliases and their associated Steam game IDs
+	List {
+		/// Only list aliases of this app type (game, dlc, demo, tool, soundtrack or other)
+		#[structopt(short = "t", long = "type")]
+		app_type: Option<AppType>,
+
+		/// Only list dormant aliases of wishlisted games that aren't owned yet
+		#[structopt(short = "w", long = "wishlist")]
+		wishlist: bool
+	},
+
+	/// Sets daily and weekly play-time limits of an alias in minutes, checked while waiting for the game (shows them without flags)
+	Limit {
+		/// The alias whose limits are set
+		alias: String,
+
+		/// Minutes the game may be played per day
+		#[structopt(short = "d", long = "daily")]
+		daily: Option<u64>,
+
+		/// Minutes the game may be played per week, starting on Monday
+		#[structopt(short = "w", long = "weekly")]
+		weekly: Option<u64>,
+
+		/// Flag to close the game once a limit is exceeded (waiting for it is then implied)
+		#[structopt(short = "e", long = "enforce", overrides_with = "no-enforce")]
+		enforce: bool,
+
+		/// Flag to only warn once a limit is exceeded
+		#[structopt(long = "no-enforce", overrides_with = "enforce")]
+		no_enforce: bool,
+
+		/// Flag to remove all limits of the alias
+		#[structopt(short = "c", long = "clear")]
+		clear: bool
+	},
+
+	/// Sets how often break reminders are shown while waiting for a game (shows them without flags)
+	Breaks {
+		/// The alias whose reminders are set
+		alias: String,
+
+		/// Minutes of continuous play between reminders (0 turns them off for the alias)
+		#[structopt(short = "e", long = "every")]
+		every: Option<u64>,
+
+		/// Minutes until a snoozed reminder is shown again
+		#[structopt(short = "s", long = "snooze")]
+		snooze: Option<u64>,
+
+		/// Flag to remove the alias' settings, so the global ones apply
+		#[structopt(short = "c", long = "clear")]
+		clear: bool
+	},
+
+	/// Manages the launch options of an alias and syncs them with the Steam client
+	Opts {
+		#[structopt(subcommand)]
+		command: OptsCommand
+	},
+
+	/// Manages a queue of games that are played one after another
+	Queue {
+		#[structopt(subcommand)]
+		command: QueueCommand
+	},
+
+	/// Runs several games at once for a while, e.g. to collect trading card drops
+	Idle {
+		/// The aliases of the games to be run
+		#[structopt(required = true, min_values = 1)]
+		aliases: Vec<String>,
+
+		/// How many games are run at the same time (at most 32, Steam's limit)
+		#[structopt(short = "m", long = "max", default_value = "32")]
+		max: usize,
+
+		/// How many hours each game is run for before it is closed
+		#[structopt(short = "H", long = "hours", default_value = "2")]
+		hours: f64,
+
+		/// Flag to skip games without trading card drops left (needs 'steam_login_secure')
+		#[structopt(short = "d", long = "drops-only")]
+		drops_only: bool
+	},
+
+	/// Shows the remaining trading card drops and badge progress of a game
+	Cards {
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: String
+	},
+
+	/// Watches the price of a game, alerting on sales with 'watch check'
+	Watch {
+		#[structopt(subcommand)]
+		command: Option<WatchCommand>,
+
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// Only alert when the price is at or below this, e.g. '9.99' (alerts on any discount otherwise)
+		#[structopt(short = "b", long = "below", parse(try_from_str = watch::parse_price))]
+		below: Option<u64>,
+
+		/// Flag to stop watching the game instead
+		#[structopt(short = "r", long = "remove", conflicts_with = "below")]
+		remove: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: Option<String>
+	},
+
+	/// Manages aliases of games on your Steam wishlist
+	Wishlist {
+		#[structopt(subcommand)]
+		command: WishlistCommand
+	},
+
+	/// Installs a Steam game via an alias or by a Steam game ID (with -i)
+	Install {
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: String
+	},
+
+	/// Prints the appmanifest of an installed Steam game (build ID, state, size, install dir)
+	Manifest {
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: String
+	},
+
+	/// Shows the live CPU, memory and GPU usage of a running game (of any running game without an alias)
+	Top {
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// Flag to show the usage once instead of refreshing it
+		#[structopt(short = "1", long = "once")]
+		once: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: Option<String>
+	},
+
+	/// Collects the last session's launch command and environment, logs and system info of a game into a zip
+	Bugreport {
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: String
+	},
+
+	/// Opens the community hub of a Steam game via an alias or by a Steam game ID (with -i)
+	Hub {
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// Flag to open the hub in the web browser instead of the Steam client
+		#[structopt(short = "b", long = "browser")]
+		browser: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: String,
+	},
+
+	/// Prints the steam:// URL that starts a game, or its store page URL (with -s)
+	Url {
+		/// Flag to use a game ID instead of an alias
+		#[structopt(short = "i", long = "id")]
+		use_id: bool,
+
+		/// Flag to print the URL of the game's store page instead
+		#[structopt(short = "s", long = "store")]
+		store: bool,
+
+		/// Flag to render the URL as a QR code in the terminal (needs 'qrencode')
+		#[structopt(short = "q", long = "qr")]
+		qr: bool,
+
+		/// The input for selecting the game (an alias or an ID with the '-i' flag)
+		#[structopt(name = "game")]
+		game_str: String
+	},
+
+	/// Starts an interactive shell for running several commands in a row
+	Shell,
+
+	/// Shows the Steam account steamlet uses, found via the 'steam_id' config key or the accounts logged in to Steam
+	Whoami {
+		/// Flag to list every account that has logged in to Steam on this machine
+		#[structopt(short = "a", long = "all")]
+		all: bool
+	},
+
+	/// Gets or sets a config value, such as 'api_key' or 'steam_id' (shows all values without a key)
+	Config {
+		/// The dotted key of the value, e.g. 'api_key'
+		key: Option<String>,
+
+		/// The new value; JSON values such as 'true' or '42' are stored as such
+		value: Option<String>,
+
+		/// Flag to remove the key instead
+		#[structopt(short = "u", long = "unset", requires = "key", conflicts_with = "value")]
+		unset: bool
+	}
+}
+
+#[derive(StructOpt, Debug, Default)]
+struct PlayArgs {
+	/// Flag to use a game ID instead of an alias
+	#[structopt(short = "i", long = "id")]
+	use_id: bool,
+
+	/// Flag to open the app in the Steam music player (automatic for soundtracks)
+	#[structopt(short = "m", long = "music")]
+	music: bool,
+
+	/// Flag to play the demo or playtest attached to the alias
+	#[structopt(short = "d", long = "demo", conflicts_with = "use-id")]
+	demo: bool,
+
+	#[structopt(flatten)]
+	flags: PlayFlags,
+
+	/// The input for selecting the game (an alias or an ID with the '-i' flag)
+	#[structopt(name = "game")]
+	game_str: String
+}
+
+/// Flags of 'play' that can also be saved as defaults of an alias
+#[derive(StructOpt, Debug, Default)]
+struct PlayFlags {
+	/// Flag to wait until the game exits
+	#[structopt(short = "w", long = "wait", overrides_with = "no-wait")]
+	wait: bool,
+
+	/// Flag to not wait until the game exits
+	#[structopt(long = "no-wait", overrides_with = "wait")]
+	no_wait: bool,
+
+	/// Flag to open Steam's Big Picture mode before starting the game
+	#[structopt(short = "b", long = "big-picture", overrides_with = "no-big-picture")]
+	big_picture: bool,
+
+	/// Flag to not open Big Picture mode
+	#[structopt(long = "no-big-picture", overrides_with = "big-picture")]
+	no_big_picture: bool,
+
+	/// Flag to turn on the desktop's do-not-disturb mode until the game exits (implies '--wait')
+	#[structopt(long = "dnd", overrides_with = "no-dnd")]
+	dnd: bool,
+
+	/// Flag to leave do-not-disturb mode alone
+	#[structopt(long = "no-dnd", overrides_with = "dnd")]
+	no_dnd: bool,
+
+	/// A power profile to switch to until the game exits, e.g. 'performance' ('none' to not switch; implies '--wait')
+	#[structopt(long = "power-profile", name = "profile")]
+	power_profile: Option<String>,
+
+	/// A CPU frequency governor to switch to until the game exits, e.g. 'performance' ('none' to not switch; implies '--wait')
+	#[structopt(long = "governor", name = "governor")]
+	governor: Option<String>,
+
+	/// Launch options to start the game with, on top of those set in Steam, e.g. '-windowed -novid' ('none' for no extra options)
+	#[structopt(long = "launch-options", name = "options", allow_hyphen_values = true)]
+	launch_options: Option<String>
+}
+
+/// Turns a pair of on/off flags into an explicit choice, if either was given
+fn flag(on: bool, off: bool) -> Option<bool> {
+	if on {
+		Some(true)
+	} else if off {
+		Some(false)
+	} else {
+		None
+	}
+}
+
+impl PlayFlags {
+	fn to_defaults(&self) -> PlayDefaults {
+		PlayDefaults {
+			wait: flag(self.wait, self.no_wait),
+			big_picture: flag(self.big_picture, self.no_big_picture),
+			dnd: flag(self.dnd, self.no_dnd),
+			power_profile: self.power_profile.clone(),
+			governor: self.governor.clone(),
+			launch_options: self.launch_options.clone()
+		}
+	}
+}
+
+#[derive(StructOpt, Debug)]
+enum QueueCommand {
+	/// Adds one or more aliases to the end of the queue
+	Add {
+		#[structopt(required = true, min_values = 1)]
+		aliases: Vec<String>
+	},
+
+	/// Removes one or more aliases from the queue
+	Remove {
+		#[structopt(required = true, min_values = 1)]
+		aliases: Vec<String>
+	},
+
+	/// Lists the queued aliases in order
+	List,
+
+	/// Plays the queued games in order, waiting for each to exit before starting the next
+	Play,
+
+	/// Removes all aliases from the queue
+	Clear
+}
+
+#[derive(StructOpt, Debug)]
+enum OptsCommand {
+	/// Shows the launch options of an alias, in steamlet and in the Steam client
+	Show {
+		alias: String
+	},
+
+	/// Sets the launch options of an alias, e.g. "PROTON_LOG=1 %command% -novid"
+	#[structopt(setting = AppSettings::AllowLeadingHyphen)]
+	Set {
+		alias: String,
+
+		options: String
+	},
+
+	/// Removes the launch options of an alias from steamlet
+	Clear {
+		alias: String
+	},
+
+	/// Takes the launch options of an alias from the Steam client
+	ImportSteam {
+		alias: String
+	},
+
+	/// Writes the launch options of an alias to the Steam client (localconfig.vdf is backed up first)
+	PushSteam {
+		alias: String
+	}
+}
+
+#[derive(StructOpt, Debug)]
+enum WatchCommand {
+	/// Checks the prices of all watched games and alerts on sales (e.g. from cron)
+	Check,
+
+	/// Lists the watched games and their thresholds
 	List
 }
 
+#[derive(StructOpt, Debug)]
+enum WishlistCommand {
+	/// Creates aliases, marked as not owned, for the games on your wishlist
+	Import {
+		/// The 64-bit Steam ID whose wishlist is imported (defaults to the current user, see 'whoami')
+		#[structopt(long = "steam-id")]
+		steam_id: Option<u64>
+	}
+}
+
 /// Run Steam games on the commandline intuitively via aliases or IDs
 #[derive(StructOpt, Debug)]
 #[structopt(
@@ -59,6 +502,39 @@ enum SteamletCommand {
 	Play a Steam game with an alias:
 		steamlet play ets2
 
+	Listen to a soundtrack in the Steam music player:
+		steamlet play --music ets2-ost
+
+	Play a game in Big Picture mode and wait until it exits:
+		steamlet play ets2 --big-picture --wait
+
+	Always play a game in Big Picture mode, unless '--no-big-picture' is given:
+		steamlet defaults ets2 --big-picture
+
+	Silence desktop notifications while playing, for one game or for all of them:
+		steamlet play ets2 --dnd
+		steamlet config defaults.play.dnd true
+
+	Use the performance power profile while playing, switching back afterwards:
+		steamlet defaults ets2 --power-profile performance
+
+	Or, without a power profile daemon, switch the CPU frequency governor:
+		steamlet defaults ets2 --governor performance
+
+	Always start a game with extra launch options, e.g. in a window:
+		steamlet defaults ets2 --launch-options "-windowed -nointro"
+
+	Attach a demo or playtest to an alias, then play it:
+		steamlet add ets2 227300 --demo 1068290
+		steamlet play ets2 --demo
+
+	Add an alias from a store URL, optionally naming it after the game:
+		steamlet add ets2 https://store.steampowered.com/app/227300/Euro_Truck_Simulator_2/
+		steamlet add https://store.steampowered.com/app/227300/Euro_Truck_Simulator_2/
+
+	Add an alias from a store URL copied in the browser:
+		steamlet add ets2 --from-clipboard
+
 	You can also use spaces in your aliases with double-quotes:
 		steamlet add "euro truck simulator 2" 227300
 
@@ -67,194 +543,1104 @@ enum SteamletCommand {
 
 	List saved aliases:
 		steamlet list
+
+	List saved aliases of games only:
+		steamlet list --type game
+
+	Limit a game to an hour a day and close it once the hour is up:
+		steamlet limit ets2 --daily 60 --enforce
+
+	Limit all games to 10 hours a week:
+		steamlet config limits.weekly 600
+
+	Get a reminder to take a break every 45 minutes of play, or every hour for all games:
+		steamlet breaks ets2 --every 45
+		steamlet config breaks.every 60
+
+	Keep launch options in steamlet and copy them to the Steam client (best with Steam closed):
+		steamlet opts set ets2 "gamemoderun %command% -nointro"
+		steamlet opts push-steam ets2
+
+	Queue up games and play them one after another:
+		steamlet queue add ets2 portal
+		steamlet queue play
+
+	Idle games for trading card drops, 5 at a time for 3 hours each:
+		steamlet idle ets2 portal csgo --max 5 --hours 3
+
+	Show the remaining card drops of a game, using your steamLoginSecure cookie:
+		steamlet config steam_login_secure <cookie>
+		steamlet cards ets2
+
+	Watch the price of a game and check for sales (add a webhook with 'config watch.webhook <url>'):
+		steamlet watch ets2 --below 4.99
+		steamlet watch check
+
+	Import your wishlist as aliases, then list what hasn't been bought yet:
+		steamlet wishlist import
+		steamlet list --wishlist
+
+	Install a game, refusing to if less than 20 GB would be left on every Steam library:
+		steamlet config disk.min_free_gb 20
+		steamlet config disk.abort true
+		steamlet install ets2
+
+	Show the build ID, state and size of an installed game:
+		steamlet manifest ets2
+
+	Watch the CPU, memory and GPU usage of the running game:
+		steamlet top
+
+	Collect logs and system info after a game crashed while waiting for it:
+		steamlet play ets2 --wait
+		steamlet bugreport ets2
+
+	Open the community hub of a game (guides, discussions, screenshots):
+		steamlet hub ets2
+
+	Show the Steam account steamlet uses, or every account that logged in on this machine:
+		steamlet whoami
+		steamlet whoami --all
+
+	Configure your Steam ID and Web API key (used e.g. for Family Sharing checks):
+		steamlet config steam_id 76561197960287930
+		steamlet config api_key <key>
+
+	Show a QR code of a game's store page to open it on your phone:
+		steamlet url ets2 --store --qr
+
+	Use screen-reader friendly output, once or always:
+		steamlet list --plain
+		steamlet config plain true
+
+	Start an interactive shell with tab-completion of commands and aliases:
+		steamlet shell
+
+	Set global defaults of any command's flags (overridden by the commandline):
+		steamlet config defaults.play.wait true
+		steamlet config defaults.list.type game
+		steamlet config defaults.remove.confirm true
+		steamlet config defaults.idle.max 5
 "#
 )]
 struct Steamlet {
+	/// Flag for screen-reader friendly output: one labeled field per line, without tables, padding or color
+	#[structopt(long = "plain", global = true)]
+	plain: bool,
+
 	#[structopt(subcommand)]
 	command: SteamletCommand
 }
 
-static DATA_FILE_NAME: &'static str = "steamlet.json";
+/// Builds the commandline parser, which doesn't use color in plain mode
+fn app<'a, 'b>(config: &Config, args: &[String]) -> App<'a, 'b> {
+	let app = Steamlet::clap();
+
+	if config.plain_output() || args.iter().any(|arg| arg == "--plain") {
+		app.setting(AppSettings::ColorNever)
+	} else {
+		app
+	}
+}
+
+/// Flags of 'play' whose global defaults are applied by 'play' itself, since
+/// the defaults saved on an alias take precedence over them
+const ALIAS_DEFAULT_FLAGS: &[&str] = &["wait", "big-picture", "dnd", "power-profile", "governor", "launch-options"];
+
+/// Adds the flags of the config's 'defaults.<command>.<flag>' keys to a
+/// commandline, right after the command, unless the flag is already given
+///
+/// Each default is checked by parsing the commandline with it, so unknown
+/// flags and invalid values are warned about and left out
+fn with_defaults(config: &Config, args: Vec<String>) -> Vec<String> {
+	let parse = |args: &[String]| Steamlet::clap().setting(AppSettings::ColorNever).get_matches_from_safe(args);
+
+	// Errors and '--help' are left to the real parse
+	let command = match parse(&args) {
+		Ok(matches) => matches.subcommand_name().unwrap_or_default().to_string(),
+		Err(_) => return args
+	};
+
+	let defaults = match config.get(&format!("defaults.{}", command)).and_then(|v| v.as_object()) {
+		Some(defaults) => defaults,
+		None => return args
+	};
 
-fn run_steam_game(game_id: u32) {
+	// Global flags such as '--plain' may come before the command
+	let position = match args.iter().skip(1).position(|arg| !arg.starts_with('-')) {
+		Some(index) => index + 2,
+		None => return args
+	};
+
+	let mut injected = Vec::new();
+
+	for (flag, value) in defaults {
+		let key = format!("defaults.{}.{}", command, flag);
+		let base = flag.strip_prefix("no-").unwrap_or(flag);
+		let given = args[position..].iter().any(|arg| {
+			[base.to_string(), format!("no-{}", base)].iter()
+				.any(|name| *arg == format!("--{}", name) || arg.starts_with(&format!("--{}=", name)))
+		});
+
+		let words = match value {
+			Value::Bool(true) => vec![format!("--{}", flag)],
+			Value::Bool(false) => vec![format!("--no-{}", flag)],
+			Value::String(s) => vec![format!("--{}", flag), s.clone()],
+			Value::Number(n) => vec![format!("--{}", flag), n.to_string()],
+			_ => {
+				println!("Warning: ignoring '{}', which must be true, false, a number or a string", key);
+				continue;
+			}
+		};
+
+		let mut trial = args.clone();
+		trial.splice(position..position, words.iter().cloned());
+
+		match parse(&trial) {
+			// Given as a short flag instead
+			Err(ref e) if e.kind == ErrorKind::UnexpectedMultipleUsage => {},
+			Err(e) if !given => {
+				let reason = e.message.lines().next().unwrap_or_default().trim_start_matches("error: ").to_string();
+				println!("Warning: ignoring '{}': {}", key, reason);
+			},
+			_ if given => {},
+			_ if command == "play" && ALIAS_DEFAULT_FLAGS.contains(&base) => {},
+			_ => injected.extend(words)
+		}
+	}
+
+	let mut args = args;
+	args.splice(position..position, injected);
+	args
+}
+
+fn run_steam_game(game_id: u32, big_picture: bool, launch_options: Option<&str>) {
 	println!("-------------------------------------------------");
-	Command::new("steam")
-		.arg(format!("steam://run/{}", game_id))
-		.spawn()
-		.expect("'steam' command failed to start");
+
+	if big_picture {
+		open_steam_url("steam://open/bigpicture".to_string());
+	}
+
+	// Steam adds the options after the ones set in its own client
+	match launch_options {
+		Some(options) => open_steam_url(format!("steam://run/{}//{}/", game_id, steam::url_encode(options))),
+		None => open_steam_url(format!("steam://run/{}", game_id))
+	}
+}
+
+fn open_soundtrack() {
+	// The music player lists every owned soundtrack; there is no URL to
+	// start a particular one
+	open_steam_url("steam://open/music".to_string());
+}
+
+fn open_community_hub(game_id: u32, browser: bool) {
+	if browser {
+		Command::new("xdg-open")
+			.arg(format!("https://steamcommunity.com/app/{}", game_id))
+			.status()
+			.expect("'xdg-open' command failed to start");
+	} else {
+		open_steam_url(format!("steam://url/GameHub/{}", game_id));
+	}
 }
 
-fn get_alias_data() -> (File, HashMap<String, u32>) {
-	// Get local data directory
-	let data_dir: PathBuf = dirs::data_local_dir().unwrap().join("steamlet");
-	let data: HashMap<String, u32>;
-	let file: File;
+/// Renders text as a QR code in the terminal using 'qrencode'
+fn print_qr_code(text: &str) -> bool {
+	Command::new("qrencode")
+		.args(["--type", "UTF8", "--margin", "2", text])
+		.status()
+		.map(|status| status.success())
+		.unwrap_or(false)
+}
 
-	// Create a new file if the local data directory does not exist
-	if !data_dir.exists() {
-		std::fs::create_dir_all(data_dir.as_path()).unwrap();
+/// Asks a yes/no question on the commandline, defaulting to no
+fn ask(question: &str) -> bool {
+	print!("{} [y/N] ", question);
+	std::io::stdout().flush().unwrap();
 
-		file = OpenOptions::new()
-			.write(true)
-			.create_new(true)
-			.open(data_dir.join(DATA_FILE_NAME).as_path())
-			.unwrap();
+	let mut answer = String::new();
+	std::io::stdin().read_line(&mut answer).unwrap_or(0);
 
-		data = HashMap::new();
+	matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Finds the Steam game ID from either an alias or an ID (when 'use_id' is set)
+fn find_game_id(use_id: bool, game_str: &str, data: &AliasData) -> Option<u32> {
+	if use_id {
+		match game_str.parse::<u32>() {
+			Ok(id) => Some(id),
+			Err(_) => {
+				println!("Steam ID must be a number");
+				None
+			}
+		}
 	} else {
-		file = OpenOptions::new()
-			.read(true)
-			.write(true)
-			.open(data_dir.join(DATA_FILE_NAME).as_path())
-			.unwrap();
-
-		let buf_reader = BufReader::new(&file);
-		// Read file contents into HashMap
-		data = serde_json::from_reader(buf_reader)
-			.unwrap_or(HashMap::new());
-
-		//println!("Found data file '{}'", data_dir.to_str().unwrap());
+		let game = &game_str.to_lowercase();
+
+		match data.aliases.get(game) {
+			Some(alias) => Some(alias.id),
+			None => {
+				println!("Could not find alias '{}'", game);
+				None
+			}
+		}
+	}
+}
+
+/// Gets the app type of an alias, fetching and saving it from the store if
+/// it isn't known yet
+fn alias_app_type(data: &mut AliasData, config: &Config, alias: &str) -> Option<AppType> {
+	let entry = data.aliases.get_mut(alias)?;
+
+	if entry.app_type.is_none() && !entry.unknown_type {
+		entry.app_type = steam::cached_app(config, entry.id).and_then(|app| app.app_type);
+
+		if entry.app_type.is_some() {
+			data.save(String::new());
+		}
 	}
 
-	(file, data)
+	data.aliases[alias].app_type
 }
 
-fn write_to_data_file(file: File, data: HashMap<String, u32>, message: String) {
-	// Create BufWriter for the file
-	let mut buf_writer = BufWriter::new(&file);
+/// Looks up the type of an alias' app in the store once it has been launched,
+/// remembering if the store doesn't know it either
+fn fetch_alias_app_type(data: &mut AliasData, alias: &str) {
+	let entry = match data.aliases.get_mut(alias) {
+		Some(entry) if entry.app_type.is_none() && !entry.unknown_type => entry,
+		_ => return
+	};
 
-	// Clear the file contents and set the cursor to position '0'
-	file.set_len(0).unwrap();
-	buf_writer.seek(SeekFrom::Start(0)).unwrap();
+	entry.app_type = store::fetch_app_type(entry.id);
+	entry.unknown_type = entry.app_type.is_none();
+	data.save(String::new());
+}
 
-	// Write data to the file
-	match serde_json::to_writer_pretty(buf_writer, &data) {
-		Ok(_) => {
-			println!("{}", message);
-			// TODO: How to flush?
-			//buf_writer.flush().unwrap();
-		},
-		Err(_) => {
-			println!("Error while writing to {}", DATA_FILE_NAME);
+/// Creates or updates an alias; without an alias, one is made from the name
+/// of the game
+fn set_alias(data: &mut AliasData, config: &Config, alias: Option<String>, link: AppLink, demo: Option<u32>) {
+	let id = link.id;
+	let mut details = None;
+
+	// The Steam client's cache is used before the store, which needs a connection
+	let cached = steam::cached_app(config, id);
+
+	let formatted: String = match (alias, link.name) {
+		(Some(alias), _) => alias.trim().to_lowercase(),
+		(None, Some(name)) => data::alias_from_name(&name),
+		(None, None) => {
+			let name = cached.as_ref().and_then(|app| app.name.clone()).or_else(|| {
+				details = store::fetch_app_details(id);
+				details.as_ref().and_then(|d| d["name"].as_str()).map(String::from)
+			});
+
+			match name {
+				Some(name) => data::alias_from_name(&name),
+				None => {
+					println!("Could not find the name of {}; give an alias for it", id);
+					return;
+				}
+			}
+		}
+	};
+
+	// Create/update the alias with the associated steam_id
+	if !formatted.is_empty() {
+		// Keep what is already known about the alias unless its ID changes
+		let mut entry = match data.aliases.remove(&formatted) {
+			Some(existing) if existing.id == id => existing,
+			_ => Alias::new(id)
+		};
+
+		if let Some(cached) = cached {
+			entry.app_type = entry.app_type.or(cached.app_type);
+			entry.name = entry.name.take().or(cached.name);
+		}
+
+		// The store is only asked if the alias had to be named after the
+		// game; otherwise the type is looked up after the first launch
+		if let Some(details) = details {
+			entry.app_type = entry.app_type.or_else(|| details["type"].as_str().map(AppType::from_store_str));
+			entry.name = entry.name.take().or_else(|| details["name"].as_str().map(String::from));
+		}
+
+		let mut kind = match entry.app_type {
+			Some(t) => format!(" ({})", t),
+			None => String::new()
+		};
+
+		if demo.is_some() {
+			entry.demo = demo;
+		}
+
+		if let Some(demo_id) = entry.demo {
+			kind += &format!(" with demo {}", demo_id);
+		}
+
+		data.aliases.insert(formatted.to_string(), entry);
+
+		let message = format!("Alias '{}' successfully set to {}{}; total aliases = {}", &formatted, id, kind, data.aliases.len());
+
+		data.save(message);
+	} else {
+		println!("Alias must not be empty");
+	}
+}
+
+/// Plays a game as requested by the 'play' command, returning whether it was
+/// started, and when it is waited for, whether it was seen until it exited
+fn play(args: &PlayArgs, data: &mut AliasData, config: &Config) -> bool {
+	let PlayArgs { use_id, music, demo, ref flags, ref game_str } = *args;
+
+	// Play steam game via the id itself or via the player-made alias
+	let mut id = match find_game_id(use_id, game_str, data) {
+		Some(id) => id,
+		None => return false
+	};
+
+	let name;
+	let app_type;
+	let game = game_str.to_lowercase();
+	let alias = if use_id { None } else { Some(game.as_str()) };
+
+	// Flags given on the commandline take precedence over the alias'
+	// defaults, which take precedence over the global defaults
+	let defaults = match data.aliases.get(&game) {
+		Some(alias) if !use_id => alias.defaults.clone(),
+		_ => PlayDefaults::default()
+	};
+	let flags = flags.to_defaults();
+	let wait = flags.wait
+		.or(defaults.wait)
+		.or_else(|| config.default_bool("play", "wait"))
+		.unwrap_or(false);
+	let big_picture = flags.big_picture
+		.or(defaults.big_picture)
+		.or_else(|| config.default_bool("play", "big-picture"))
+		.unwrap_or(false);
+	let dnd = flags.dnd
+		.or(defaults.dnd)
+		.or_else(|| config.default_bool("play", "dnd"))
+		.unwrap_or(false);
+	let power_profile = flags.power_profile
+		.or(defaults.power_profile)
+		.or_else(|| config.default_str("play", "power-profile").map(String::from))
+		.filter(|profile| profile != "none");
+	let governor = flags.governor
+		.or(defaults.governor)
+		.or_else(|| config.default_str("play", "governor").map(String::from))
+		.filter(|governor| governor != "none");
+	let launch_options = flags.launch_options
+		.or(defaults.launch_options)
+		.or_else(|| config.default_str("play", "launch-options").map(String::from))
+		.filter(|options| options != "none" && !options.trim().is_empty());
+
+	let limits = alias.map(|alias| data.aliases[alias].limits.clone()).unwrap_or_default();
+	let limit_hook = LimitHook::new(alias, &limits, &Limits::global(config));
+
+	// Limits can only be enforced, and system settings only be restored,
+	// while the game is waited for
+	let wait = wait || dnd || power_profile.is_some() || governor.is_some() || limit_hook.as_ref().is_some_and(LimitHook::enforced);
+
+	if use_id {
+		name = format!("application with ID '{}'", id);
+		app_type = steam::cached_app(config, id).and_then(|app| app.app_type);
+	} else if demo {
+		match data.aliases[&game].demo {
+			Some(demo_id) => id = demo_id,
+			None => {
+				println!("Alias '{}' has no demo attached; set one with 'steamlet set {} {} --demo <demo_id>'", game, game, id);
+				return false;
+			}
+		}
+
+		name = format!("the demo of {} ({})", game, id);
+		app_type = Some(AppType::Demo);
+	} else {
+		name = format!("{} ({})", game, id);
+		app_type = alias_app_type(data, config, &game);
+
+		if data.aliases[&game].not_owned {
+			if steam::find_manifest(config, id).is_some() {
+				// The game has been bought and installed since it was imported
+				data.aliases.get_mut(&game).unwrap().not_owned = false;
+				data.save(String::new());
+			} else {
+				println!("Note: '{}' is on your wishlist and may not be owned yet", game);
+			}
 		}
 	}
+
+	if music || app_type == Some(AppType::Soundtrack) {
+		println!("Opening {} in the Steam music player", name);
+		open_soundtrack();
+		true
+	} else {
+		if let Some(t) = app_type.filter(|t| !t.is_launchable()) {
+			println!("Warning: {} is a {} and may not be launchable", name, t);
+		}
+
+		// Installed games are updated in their own library, others are
+		// installed in whichever one the user picks
+		let libraries = match steam::find_manifest(config, id) {
+			Some((path, _)) => path.parent().map(|dir| vec![dir.to_path_buf()]).unwrap_or_default(),
+			None => steam::library_dirs(config)
+		};
+
+		if !disk::check(config, &libraries, "launching") {
+			return false;
+		}
+
+		// Bounded by curl's timeout, so it delays the launch by a few seconds at most
+		family::warn_if_lender_playing(config, id);
+
+		// Created before the launch, so that only the game's new processes
+		// belong to the session
+		let session = Session::new(id, name.clone(), alias.map(String::from));
+
+		match launch_options {
+			Some(ref options) => println!("Starting {} with launch options: {}", name, options),
+			None => println!("Starting {}", name)
+		}
+
+		run_steam_game(id, big_picture, launch_options.as_deref());
+
+		// The store lookup is done once the game is on its way
+		if let Some(alias) = alias.filter(|_| !demo) {
+			fetch_alias_app_type(data, alias);
+		}
+
+		if !wait {
+			return true;
+		}
+
+		let mut hooks: Vec<Box<dyn SessionHook>> = vec![Box::new(playtime::Recorder), Box::new(crash::CrashHook::new(config))];
+
+		if let Some(hook) = limit_hook {
+			hooks.push(Box::new(hook));
+		}
+
+		if dnd {
+			hooks.push(Box::new(dnd::DndHook::default()));
+		}
+
+		if let Some(profile) = power_profile {
+			hooks.push(Box::new(power::PowerProfileHook::new(&profile)));
+		}
+
+		if let Some(governor) = governor {
+			hooks.push(Box::new(power::GovernorHook::new(&governor)));
+		}
+
+		let breaks = alias.map(|alias| data.aliases[alias].breaks.clone()).unwrap_or_default();
+
+		if let Some(hook) = BreakHook::new(&breaks, &Breaks::global(config)) {
+			hooks.push(Box::new(hook));
+		}
+
+		session::run(session, &mut hooks).is_some()
+	}
 }
 
 fn main() {
-	let args = Steamlet::from_args();
+	let mut config = Config::load();
+	let argv: Vec<String> = std::env::args().collect();
+	let argv = with_defaults(&config, argv);
+	let args = Steamlet::from_clap(&app(&config, &argv).get_matches_from(&argv));
+	let mut data = AliasData::load();
+
+	run(args, &mut data, &mut config);
+}
+
+fn run(args: Steamlet, data: &mut AliasData, config: &mut Config) {
+	let plain = args.plain || config.plain_output();
 
 	//println!("{:?}\n\n-----------", args);
 	match args.command {
-		SteamletCommand::Play { use_id, game_str } => {
-			if use_id {
-				// Play steam game via the id itself
-				match game_str.parse::<u32>() {
-					Ok(id) => {
-						println!("Starting application with ID '{}'", id);
-						run_steam_game(id);
-					}
-					Err(_) => println!("Steam ID must be a number")
+		SteamletCommand::Play(args) => {
+			play(&args, data, config);
+		},
+		SteamletCommand::Defaults { alias, flags, clear } => {
+			let game = alias.to_lowercase();
+			let new = flags.to_defaults();
+
+			let entry = match data.aliases.get_mut(&game) {
+				Some(entry) => entry,
+				None => {
+					println!("Could not find alias '{}'", game);
+					return;
 				}
+			};
+
+			if clear {
+				entry.defaults = PlayDefaults::default();
 			} else {
-				// Play steam game via the player-made alias
-				let data: HashMap<String, u32> = get_alias_data().1;
-				let game = &game_str.to_lowercase();
-
-				match data.get(game) {
-					Some(id) => { 
-						println!("Starting {} ({})", game, *id);
-						run_steam_game(*id);
-					}
-					None => println!("Could not find alias '{}'", game)
-				}
+				entry.defaults.wait = new.wait.or(entry.defaults.wait);
+				entry.defaults.big_picture = new.big_picture.or(entry.defaults.big_picture);
+				entry.defaults.dnd = new.dnd.or(entry.defaults.dnd);
+				entry.defaults.power_profile = new.power_profile.clone().or(entry.defaults.power_profile.take());
+				entry.defaults.governor = new.governor.clone().or(entry.defaults.governor.take());
+				entry.defaults.launch_options = new.launch_options.clone().or(entry.defaults.launch_options.take());
 			}
+
+			let mut fields = vec![("Alias", game.clone())];
+			fields.extend(entry.defaults.fields());
+
+			if clear || new != PlayDefaults::default() {
+				data.save(format!("Defaults of '{}' successfully set", game));
+			}
+
+			output::print_fields(&fields, plain);
 		},
-		SteamletCommand::Set { alias, id } => {
-			// Get the file and parsed data
-			let tuple = get_alias_data();
-			let file: File = tuple.0;
-			let mut data: HashMap<String, u32> = tuple.1;
+		SteamletCommand::Limit { alias, daily, weekly, enforce, no_enforce, clear } => {
+			let game = alias.to_lowercase();
+
+			let entry = match data.aliases.get_mut(&game) {
+				Some(entry) => entry,
+				None => {
+					println!("Could not find alias '{}'", game);
+					return;
+				}
+			};
+
+			let enforce = flag(enforce, no_enforce);
 
-			// Create/update the alias with the associated steam_id
-			let formatted: String = alias.trim().to_lowercase();
+			if clear {
+				entry.limits = Limits::default();
+			} else {
+				entry.limits.daily = daily.or(entry.limits.daily);
+				entry.limits.weekly = weekly.or(entry.limits.weekly);
+				entry.limits.enforce = enforce.or(entry.limits.enforce);
+			}
+
+			let playtime = playtime::Playtime::load();
+			let mut fields = vec![("Alias", game.clone())];
+			fields.extend(entry.limits.fields());
+			fields.push(("Played today", session::format_duration(playtime.played_since(playtime::start_of_day(), Some(&game)))));
+			fields.push(("Played this week", session::format_duration(playtime.played_since(playtime::start_of_week(), Some(&game)))));
+
+			if clear || daily.is_some() || weekly.is_some() || enforce.is_some() {
+				data.save(format!("Limits of '{}' successfully set", game));
+			}
 
-			if formatted.len() > 0 {
-				data.insert(formatted.to_string(), id);
+			output::print_fields(&fields, plain);
+		},
+		SteamletCommand::Breaks { alias, every, snooze, clear } => {
+			let game = alias.to_lowercase();
 
-				let message = format!("Alias '{}' successfully set to {}; total aliases = {}", &formatted, id, data.len());
+			let entry = match data.aliases.get_mut(&game) {
+				Some(entry) => entry,
+				None => {
+					println!("Could not find alias '{}'", game);
+					return;
+				}
+			};
 
-				write_to_data_file(file, data, message);
+			if clear {
+				entry.breaks = Breaks::default();
 			} else {
-				println!("Alias must not be empty");
+				entry.breaks.every = every.or(entry.breaks.every);
+				entry.breaks.snooze = snooze.or(entry.breaks.snooze);
 			}
+
+			let mut fields = vec![("Alias", game.clone())];
+			fields.extend(entry.breaks.fields());
+			fields.push(("Globally", Breaks::global(config).fields().iter()
+				.map(|(label, value)| format!("{} {}", label.to_lowercase(), value))
+				.collect::<Vec<_>>()
+				.join(", ")));
+
+			if clear || every.is_some() || snooze.is_some() {
+				data.save(format!("Break reminders of '{}' successfully set", game));
+			}
+
+			output::print_fields(&fields, plain);
 		},
-		SteamletCommand::Remove { mut aliases } => {
-			// Get the file and parsed data
-			let tuple = get_alias_data();
-			let file: File = tuple.0;
-			let mut data: HashMap<String, u32> = tuple.1;
+		SteamletCommand::Set { alias, target, from_clipboard, demo } => {
+			// A store URL on its own is enough, the alias is then made from the game's name
+			let (alias, target) = if from_clipboard {
+				match clipboard::read() {
+					Some(text) => (alias, text),
+					None => {
+						println!("Could not read the clipboard; is wl-clipboard, xclip or xsel installed?");
+						return;
+					}
+				}
+			} else {
+				match target {
+					Some(target) => (alias, target),
+					None => (None, alias.unwrap())
+				}
+			};
 
+			match store::parse_app_link(&target) {
+				Some(link) => set_alias(data, config, alias, link, demo),
+				None if from_clipboard => println!("Could not find a Steam game ID or store URL in the clipboard"),
+				None => println!("Steam ID must be a number or a store URL")
+			}
+		},
+		SteamletCommand::Remove { mut aliases, confirm, no_confirm } => {
 			// Filter out the list of aliases that don't exist in 'data'
 			// We use the 'aliases' list to print out what did get successfully
 			// removed
 			aliases.retain(|a| {
-				let b = data.contains_key(a);
+				let b = data.aliases.contains_key(a);
 
 				if !b {
 					println!("Alias '{}' not found", a);
 				}
 
-				return b;
+				b
 			});
 
 			// If there are existing aliases, remove them
-			if aliases.len() > 0 {
-				// Filter out the entries in 'data' whose key exists in 'aliases'
-				data.retain(|key, _| {
-					return !aliases.contains(key);
-				});
-
+			if !aliases.is_empty() {
 				let mut list: String = String::new();
 				let mut first = true;
 
-				for item in aliases {
+				for item in &aliases {
 					if !first {
 						list += ", ";
 					}
-					list += &item;
+					list += item;
 					first = false;
 				}
 
-				let message = format!("Aliases '{}' successfully removed; total aliases = {}", list, data.len());
+				let confirm = flag(confirm, no_confirm).unwrap_or(false);
+
+				if confirm && !ask(&format!("Remove aliases '{}'?", list)) {
+					println!("Nothing removed; total aliases = {}", data.aliases.len());
+					return;
+				}
 
-				write_to_data_file(file, data, message);
+				// Filter out the entries in 'data' whose key exists in 'aliases'
+				data.aliases.retain(|key, _| {
+					!aliases.contains(key)
+				});
+
+				let message = format!("Aliases '{}' successfully removed; total aliases = {}", list, data.aliases.len());
+
+				data.save(message);
 			} else {
-				println!("Nothing to be removed; total aliases = {}", data.len());
+				println!("Nothing to be removed; total aliases = {}", data.aliases.len());
 			}
 		},
-		SteamletCommand::List => {
-			// Get the file and parsed data
-			let tuple = get_alias_data();
-			let data: HashMap<String, u32> = tuple.1;
+		SteamletCommand::List { app_type, wishlist } => {
 			let tab_size = 4.0;
 			let num_tabs: usize = 4;
 
-			println!("Path: {}\n", dirs::data_local_dir().unwrap().join("steamlet").join(DATA_FILE_NAME).to_str().unwrap());
+			if plain {
+				output::print_record(&[("Path", AliasData::path().to_str().unwrap().to_string())]);
+			} else {
+				println!("Path: {}\n", AliasData::path().to_str().unwrap());
+			}
 
 			// Sort results alphabetically
-			let mut sorted: Vec<_> = data.into_iter().collect();
-			sorted.sort_by(|x,y| x.0.cmp(&y.0));
+			let mut sorted: Vec<_> = data.aliases.iter()
+				.filter(|kv| app_type.is_none() || kv.1.app_type == app_type)
+				.filter(|kv| !wishlist || kv.1.not_owned)
+				.collect();
+			sorted.sort_by(|x,y| x.0.cmp(y.0));
 
 			for kv in &sorted {
+				if plain {
+					let mut fields = vec![("Alias", kv.0.to_string()), ("ID", kv.1.id.to_string())];
+
+					if let Some(t) = kv.1.app_type {
+						fields.push(("Type", t.to_string()));
+					}
+
+					if kv.1.not_owned {
+						fields.push(("Owned", "no, on wishlist".to_string()));
+					}
+
+					output::print_record(&fields);
+					continue;
+				}
+
 				let calc = ((kv.0.len() as f64) / tab_size).round() as usize;
-				let spaces: String = std::iter::repeat("\t").take(num_tabs).collect::<String>();
+				let spaces: String = "\t".repeat(num_tabs);
+				let mut kind = match kv.1.app_type {
+					Some(t) => format!("\t{}", t),
+					None => String::new()
+				};
+
+				if kv.1.not_owned {
+					kind += "\t(wishlist)";
+				}
 
 				// If the alias is longer than the default of 'num_tabs' tabs, put the id on a separate line
 				if calc > num_tabs {
 					println!("{}", kv.0);
-					println!("{}{}", spaces, kv.1);
+					println!("{}{}{}", spaces, kv.1.id, kind);
 				} else {
-					println!("{}{}{}", kv.0, spaces, kv.1);
+					println!("{}{}{}{}", kv.0, spaces, kv.1.id, kind);
+				}
+			}
+		},
+		SteamletCommand::Install { use_id, game_str } => {
+			if let Some(id) = find_game_id(use_id, &game_str, data) {
+				if let Some((path, _)) = steam::find_manifest(config, id) {
+					println!("{} is already installed ({})", id, path.display());
+				} else if disk::check(config, &steam::library_dirs(config), "installing") {
+					println!("Installing {}", id);
+					open_steam_url(format!("steam://install/{}", id));
+				}
+			}
+		},
+		SteamletCommand::Manifest { use_id, game_str } => {
+			if let Some(id) = find_game_id(use_id, &game_str, data) {
+				if !manifest::print(config, id, plain) {
+					println!("{} is not installed in any Steam library", id);
+				}
+			}
+		},
+		SteamletCommand::Top { use_id, once, game_str } => {
+			let id = match game_str {
+				Some(game_str) => match find_game_id(use_id, &game_str, data) {
+					Some(id) => id,
+					None => return
+				},
+				None => match session::running_games()[..] {
+					[] => {
+						println!("No Steam game is running");
+						return;
+					},
+					[id] => id,
+					[id, ..] => {
+						println!("Several Steam games are running; showing {} (pick one with an alias or '-i')", id);
+						id
+					}
+				}
+			};
+
+			// Named after its alias, or what Steam calls it
+			let name = data.aliases.iter()
+				.find(|(_, alias)| alias.id == id)
+				.map(|(alias, _)| alias.clone())
+				.or_else(|| steam::cached_app(config, id).and_then(|app| app.name))
+				.unwrap_or_else(|| format!("application {}", id));
+
+			top::run(&name, id, once, plain);
+		},
+		SteamletCommand::Bugreport { use_id, game_str } => {
+			if let Some(id) = find_game_id(use_id, &game_str, data) {
+				let alias = if use_id { None } else { data.aliases.get(&game_str.to_lowercase()) };
+				let name = if use_id { id.to_string() } else { game_str.to_lowercase() };
+
+				println!("Collecting a bug report for {}...", name);
+
+				match bugreport::create(config, id, &name, alias.and_then(|a| a.launch_options.as_deref())) {
+					Ok(report) => {
+						println!("Bug report saved to {}, containing:", report.zip.display());
+
+						for line in &report.contents {
+							println!("  {}", line);
+						}
+
+						println!("Check it for anything else private before sharing it");
+					},
+					Err(e) => println!("{}", e)
+				}
+			}
+		},
+		SteamletCommand::Hub { use_id, browser, game_str } => {
+			if let Some(id) = find_game_id(use_id, &game_str, data) {
+				println!("Opening community hub for {}", id);
+				open_community_hub(id, browser);
+			}
+		},
+		SteamletCommand::Opts { command } => {
+			let alias = match &command {
+				OptsCommand::Show { alias } | OptsCommand::Set { alias, .. } | OptsCommand::Clear { alias }
+				| OptsCommand::ImportSteam { alias } | OptsCommand::PushSteam { alias } => alias.to_lowercase()
+			};
+
+			let entry = match data.aliases.get_mut(&alias) {
+				Some(entry) => entry,
+				None => {
+					println!("Could not find alias '{}'", alias);
+					return;
+				}
+			};
+
+			let id = entry.id;
+			let describe = |options: Option<&str>| options.map_or("none".to_string(), |o| format!("'{}'", o));
+
+			match command {
+				OptsCommand::Show { .. } => {
+					let steam = match localconfig::read_launch_options(config, id) {
+						Ok(options) => describe(options.as_deref()),
+						Err(e) => format!("unknown ({})", e)
+					};
+
+					output::print_fields(&[
+						("steamlet", describe(entry.launch_options.as_deref())),
+						("Steam", steam)
+					], plain);
+				},
+				OptsCommand::Set { options, .. } => {
+					entry.launch_options = Some(options).filter(|o| !o.trim().is_empty());
+
+					let message = format!("Launch options of '{}' successfully set to {}; push them with 'steamlet opts push-steam {}'", alias, describe(entry.launch_options.as_deref()), alias);
+					data.save(message);
+				},
+				OptsCommand::Clear { .. } => {
+					entry.launch_options = None;
+					data.save(format!("Launch options of '{}' successfully removed", alias));
+				},
+				OptsCommand::ImportSteam { .. } => match localconfig::read_launch_options(config, id) {
+					Ok(None) => println!("Steam has no launch options for '{}'; kept steamlet's: {}", alias, describe(entry.launch_options.as_deref())),
+					Ok(options) => {
+						let message = format!("Launch options of '{}' successfully imported from Steam: {}", alias, describe(options.as_deref()));
+
+						entry.launch_options = options;
+						data.save(message);
+					},
+					Err(e) => println!("{}", e)
+				},
+				OptsCommand::PushSteam { .. } => {
+					// Steam writes its own copy of localconfig.vdf when it exits
+					if steam::is_running() && !ask("Steam is running and may overwrite the change when it exits; push anyway?") {
+						return;
+					}
+
+					match localconfig::write_launch_options(config, id, entry.launch_options.as_deref()) {
+						Ok(backup) => println!("Launch options of '{}' successfully pushed to Steam: {} (backup at {})", alias, describe(entry.launch_options.as_deref()), backup.display()),
+						Err(e) => println!("{}", e)
+					}
+				}
+			}
+		},
+		SteamletCommand::Queue { command } => {
+			let mut queue = Queue::load();
+
+			match command {
+				QueueCommand::Add { aliases } => {
+					for alias in aliases {
+						let alias = alias.to_lowercase();
+
+						if data.aliases.contains_key(&alias) {
+							queue.aliases.push(alias);
+						} else {
+							println!("Alias '{}' not found", alias);
+						}
+					}
+
+					queue.save(format!("Queue: {}", queue.aliases.join(", ")));
+				},
+				QueueCommand::Remove { aliases } => {
+					for alias in aliases {
+						let alias = alias.to_lowercase();
+
+						if queue.aliases.contains(&alias) {
+							queue.aliases.retain(|queued| *queued != alias);
+						} else {
+							println!("Alias '{}' is not queued", alias);
+						}
+					}
+
+					queue.save(format!("Queue: {}", queue.aliases.join(", ")));
+				},
+				QueueCommand::List => {
+					for (i, alias) in queue.aliases.iter().enumerate() {
+						if plain {
+							output::print_record(&[("Position", (i + 1).to_string()), ("Alias", alias.to_string())]);
+						} else {
+							println!("{}.\t{}", i + 1, alias);
+						}
+					}
+				},
+				QueueCommand::Play => {
+					while let Some(alias) = queue.aliases.first().cloned() {
+						if !data.aliases.contains_key(&alias) {
+							println!("Alias '{}' no longer exists; removing it from the queue", alias);
+							queue.aliases.remove(0);
+							queue.save(String::new());
+							continue;
+						}
+
+						let args = PlayArgs {
+							flags: PlayFlags { wait: true, ..PlayFlags::default() },
+							game_str: alias,
+							..PlayArgs::default()
+						};
+
+						// Stop if a game doesn't start or waiting for it is
+						// interrupted, so it can be played again later
+						if !play(&args, data, config) {
+							println!("Stopping the queue; {} games left", queue.aliases.len());
+							return;
+						}
+
+						queue.aliases.remove(0);
+						queue.save(String::new());
+					}
+
+					println!("The queue is empty");
+				},
+				QueueCommand::Clear => {
+					queue.aliases.clear();
+					queue.save("Queue successfully cleared".to_string());
+				}
+			}
+		},
+		SteamletCommand::Idle { aliases, max, hours, drops_only } => {
+			// Also rejects NaN, infinity and durations too long to represent
+			let duration = match std::time::Duration::try_from_secs_f64(hours * 3600.0) {
+				Ok(duration) if !duration.is_zero() => duration,
+				_ => {
+					println!("Hours must be a positive number");
+					return;
+				}
+			};
+
+			let games: Vec<(String, u32)> = aliases.iter()
+				.filter_map(|alias| {
+					let alias = alias.to_lowercase();
+					find_game_id(false, &alias, data).map(|id| (alias, id))
+				})
+				.filter(|(alias, id)| {
+					// Games whose drops can't be checked are kept
+					let done = drops_only && cards::fetch_drops_remaining(config, *id) == Some(0);
+
+					if done {
+						println!("{} has no card drops left; skipping", alias);
+					}
+
+					!done
+				})
+				.collect();
+
+			if !games.is_empty() {
+				idle::idle(&games, max, duration);
+			}
+		},
+		SteamletCommand::Cards { use_id, game_str } => {
+			if let Some(id) = find_game_id(use_id, &game_str, data) {
+				cards::print_status(config, &format!("{} ({})", game_str.to_lowercase(), id), id, plain);
+			}
+		},
+		SteamletCommand::Watch { command, use_id, below, remove, game_str } => {
+			let mut watchlist = watch::Watchlist::load();
+
+			match (command, game_str) {
+				(Some(WatchCommand::Check), _) => watchlist.check(config),
+				(Some(WatchCommand::List), _) => watchlist.list(plain),
+				(None, Some(game_str)) => {
+					if let Some(id) = find_game_id(use_id, &game_str, data) {
+						if remove {
+							watchlist.remove(id);
+						} else {
+							watchlist.add(id, game_str.to_lowercase(), below);
+						}
+					}
+				},
+				(None, None) => println!("Give a game to watch, or 'check' or 'list'")
+			}
+		},
+		SteamletCommand::Wishlist { command: WishlistCommand::Import { steam_id } } => {
+			wishlist::import(config, data, steam_id);
+		},
+		SteamletCommand::Url { use_id, store, qr, game_str } => {
+			if let Some(id) = find_game_id(use_id, &game_str, data) {
+				let url = if store {
+					format!("https://store.steampowered.com/app/{}/", id)
+				} else {
+					format!("steam://run/{}", id)
+				};
+
+				println!("{}", url);
+
+				if qr && !print_qr_code(&url) {
+					println!("Could not render a QR code; is 'qrencode' installed?");
+				}
+			}
+		},
+		SteamletCommand::Shell => {
+			shell::run(data, config, plain, run);
+		},
+		SteamletCommand::Whoami { all } => {
+			let current = steam::find_current_user(config);
+			let users = steam::login_users(config);
+
+			if all {
+				if users.is_empty() {
+					println!("No accounts have logged in to Steam on this machine");
+				}
+
+				for user in &users {
+					let marker = if current.map(|(id, _)| id) == Some(user.steam_id) { "*" } else { " " };
+					let persona = user.persona_name.as_ref().map_or(String::new(), |name| format!(" ({})", name));
+
+					if plain {
+						output::print_record(&[
+							("Account", format!("{}{}", user.account_name, persona)),
+							("Steam ID", user.steam_id.to_string()),
+							("Current", (marker == "*").to_string())
+						]);
+					} else {
+						println!("{} {}  {}{}", marker, user.steam_id, user.account_name, persona);
+					}
+				}
+
+				return;
+			}
+
+			let (steam_id, source) = match current {
+				Some(current) => current,
+				None => {
+					println!("Could not find a Steam account; log in to Steam or set one with 'steamlet config steam_id <id>'");
+					return;
+				}
+			};
+
+			let user = users.iter().find(|user| user.steam_id == steam_id);
+			let mut fields = vec![("Steam ID", steam_id.to_string())];
+
+			if let Some(user) = user {
+				fields.push(("Account", user.account_name.clone()));
+
+				if let Some(name) = &user.persona_name {
+					fields.push(("Name", name.clone()));
+				}
+			}
+
+			fields.push(("Found via", source.to_string()));
+
+			if let Some(dir) = steam::userdata_dir_of(config, steam_id) {
+				fields.push(("Userdata", dir.display().to_string()));
+			}
+
+			output::print_fields(&fields, plain);
+		},
+		SteamletCommand::Config { key, value, unset } => {
+			match (key, value) {
+				(None, _) => {
+					println!("Path: {}\n", Config::path().to_str().unwrap());
+					println!("{}", serde_json::to_string_pretty(config.values()).unwrap());
+				},
+				(Some(key), None) if unset => {
+					if !config.unset(&key) {
+						println!("Key '{}' not found", key);
+					} else if let Err(e) = config.save() {
+						println!("{}", e);
+					} else {
+						println!("Key '{}' successfully removed", key);
+					}
+				},
+				(Some(key), None) => match config.get(&key) {
+					Some(value) => println!("{}", value),
+					None => println!("Key '{}' not found", key)
+				},
+				(Some(key), Some(value)) => {
+					config.set(&key, config::parse_value(&value));
+
+					match config.save() {
+						Ok(_) => println!("Key '{}' successfully set to {}", key, config.get(&key).unwrap()),
+						Err(e) => println!("{}", e)
+					}
 				}
 			}
 		},
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use steamlet::vdf::Vdf;
+
+use crate::config::Config;
+use crate::output;
+use crate::playtime;
+use crate::steam;
+
+/// The bits of an appmanifest's 'StateFlags' field
+const STATE_FLAGS: &[(u64, &str)] = &[
+	(1 << 0, "uninstalled"),
+	(1 << 1, "update required"),
+	(1 << 2, "fully installed"),
+	(1 << 3, "encrypted"),
+	(1 << 4, "locked"),
+	(1 << 5, "files missing"),
+	(1 << 6, "app running"),
+	(1 << 7, "files corrupt"),
+	(1 << 8, "update running"),
+	(1 << 9, "update paused"),
+	(1 << 10, "update started"),
+	(1 << 11, "uninstalling"),
+	(1 << 12, "backup running"),
+	(1 << 16, "reconfiguring"),
+	(1 << 17, "validating"),
+	(1 << 18, "adding files"),
+	(1 << 19, "preallocating"),
+	(1 << 20, "downloading"),
+	(1 << 21, "staging"),
+	(1 << 22, "committing"),
+	(1 << 23, "update stopping")
+];
+
+/// Describes the set bits of a 'StateFlags' value, e.g. "4 (fully installed)"
+pub fn describe_state(flags: u64) -> String {
+	let names: Vec<&str> = STATE_FLAGS.iter()
+		.filter(|(bit, _)| flags & bit != 0)
+		.map(|(_, name)| *name)
+		.collect();
+
+	if names.is_empty() {
+		flags.to_string()
+	} else {
+		format!("{} ({})", flags, names.join(", "))
+	}
+}
+
+/// Prints the fields of an installed app's appmanifest, returning false if it
+/// isn't installed
+pub fn print(config: &Config, id: u32, plain: bool) -> bool {
+	let (path, manifest) = match steam::find_manifest(config, id) {
+		Some(found) => found,
+		None => return false
+	};
+
+	let field = |key: &str| manifest.get_path(&["AppState", key]).and_then(Vdf::as_str);
+	let number = |key: &str| field(key).and_then(|value| value.parse::<u64>().ok());
+
+	let install_dir = field("installdir").map(|dir| {
+		path.parent().unwrap_or_else(|| Path::new("")).join("common").join(dir).display().to_string()
+	});
+
+	let fields = [
+		("Name", field("name").map(String::from)),
+		("App ID", Some(id.to_string())),
+		("Build ID", field("buildid").map(String::from)),
+		("State", number("StateFlags").map(describe_state)),
+		("Size on disk", number("SizeOnDisk").map(output::format_size)),
+		("Last updated", number("LastUpdated").filter(|t| *t > 0).map(playtime::format_date)),
+		("Last played", number("LastPlayed").filter(|t| *t > 0).map(playtime::format_date)),
+		("Install dir", install_dir),
+		("Manifest", Some(path.display().to_string()))
+	];
+
+	let fields: Vec<(&str, String)> = fields.iter()
+		.filter_map(|(label, value)| value.clone().map(|value| (*label, value)))
+		.collect();
+
+	output::print_fields(&fields, plain);
+
+	true
+}
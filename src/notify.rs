@@ -0,0 +1,42 @@
+use std::process::{Command, Stdio};
+
+use crate::store;
+
+/// Shows a desktop notification via 'notify-send', returning whether it worked
+pub fn desktop(summary: &str, body: &str) -> bool {
+	Command::new("notify-send")
+		.args(["--app-name", "steamlet", summary, body])
+		.stderr(Stdio::null())
+		.status()
+		.map(|status| status.success())
+		.unwrap_or(false)
+}
+
+/// Posts a message to a webhook as JSON; both 'text' (Slack, Mattermost) and
+/// 'content' (Discord) are set so most services accept it
+pub fn webhook(url: &str, message: &str) -> bool {
+	let body = serde_json::json!({ "text": message, "content": message }).to_string();
+
+	// The URL holds the webhook's token, so it mustn't be a curl argument
+	store::post(url, &["Content-Type: application/json"], &body).is_some()
+}
+
+/// Shows a desktop notification with a single action button and waits until
+/// it is closed, returning whether the action was chosen
+///
+/// notify-send before libnotify 0.7.10 rejects '--wait' and '--action', in
+/// which case a plain notification is shown instead.
+pub fn desktop_with_action(summary: &str, body: &str, action: &str) -> bool {
+	let output = Command::new("notify-send")
+		.args(["--app-name", "steamlet", "--wait", &format!("--action=action={}", action), summary, body])
+		.stderr(Stdio::null())
+		.output();
+
+	match output {
+		Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout).trim() == "action",
+		_ => {
+			desktop(summary, body);
+			false
+		}
+	}
+}
@@ -0,0 +1,41 @@
+/// Prints labeled fields with their values aligned in a column
+pub fn print_fields(fields: &[(&str, String)], plain: bool) {
+	if plain {
+		print_record(fields);
+		return;
+	}
+
+	let width = fields.iter().map(|(label, _)| label.len()).max().unwrap_or(0) + 2;
+
+	for (label, value) in fields {
+		println!("{:width$}{}", format!("{}:", label), value, width = width);
+	}
+}
+
+/// Prints one clearly labeled field per line, followed by a blank line to
+/// separate records; used instead of tables and padding in plain mode
+pub fn print_record(fields: &[(&str, String)]) {
+	for (label, value) in fields {
+		println!("{}: {}", label, value);
+	}
+
+	println!();
+}
+
+/// Formats a number of bytes as e.g. "12.3 GB"
+pub fn format_size(bytes: u64) -> String {
+	const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+	let mut size = bytes as f64;
+	let mut unit = 0;
+
+	while size >= 1024.0 && unit < UNITS.len() - 1 {
+		size /= 1024.0;
+		unit += 1;
+	}
+
+	if unit == 0 {
+		format!("{} B", bytes)
+	} else {
+		format!("{:.1} {}", size, UNITS[unit])
+	}
+}
@@ -0,0 +1,172 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use serde::{Serialize, Deserialize};
+
+use crate::data;
+use crate::session::{Session, SessionHook};
+
+static PLAYTIME_FILE_NAME: &str = "playtime.json";
+
+pub const DAY: u64 = 24 * 60 * 60;
+
+/// A finished session of a game that was waited for
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PlayedSession {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub alias: Option<String>,
+	pub id: u32,
+	/// When the game started, in seconds since the Unix epoch
+	pub start: u64,
+	pub seconds: u64
+}
+
+/// The history of sessions, used for play-time limits
+pub struct Playtime {
+	pub sessions: Vec<PlayedSession>
+}
+
+impl Playtime {
+	pub fn path() -> PathBuf {
+		data::data_dir().join(PLAYTIME_FILE_NAME)
+	}
+
+	pub fn load() -> Playtime {
+		let sessions = fs::read_to_string(Playtime::path())
+			.ok()
+			.and_then(|text| serde_json::from_str(&text).ok())
+			.unwrap_or_default();
+
+		Playtime { sessions }
+	}
+
+	pub fn save(&self) {
+		if fs::write(Playtime::path(), serde_json::to_string_pretty(&self.sessions).unwrap()).is_err() {
+			println!("Error while writing to {}", PLAYTIME_FILE_NAME);
+		}
+	}
+
+	/// Sums up the time played since a point in time, either of one alias or
+	/// of all games
+	///
+	/// Sessions that began before that point only count with the part played
+	/// after it, e.g. one that went past midnight for the daily total
+	pub fn played_since(&self, since: u64, alias: Option<&str>) -> Duration {
+		let now = now();
+		let seconds = self.sessions.iter()
+			.filter(|s| alias.is_none() || s.alias.as_deref() == alias)
+			.map(|s| (s.start + s.seconds).min(now).saturating_sub(s.start.max(since)))
+			.sum();
+
+		Duration::from_secs(seconds)
+	}
+}
+
+/// Seconds since the Unix epoch
+pub fn now() -> u64 {
+	SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_secs()
+}
+
+/// Breaks a Unix timestamp down into local time, with the UTC offset that
+/// applied at that moment
+fn local_time(timestamp: u64) -> libc::tm {
+	let time = timestamp as libc::time_t;
+	let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+
+	unsafe { libc::localtime_r(&time, &mut tm) };
+	tm
+}
+
+/// Turns local time back into a Unix timestamp, normalizing fields that are
+/// out of range, such as a negative day of the month
+fn from_local_time(mut tm: libc::tm) -> u64 {
+	// Lets mktime find whether daylight saving time applies at that moment
+	tm.tm_isdst = -1;
+
+	unsafe { libc::mktime(&mut tm) }.max(0) as u64
+}
+
+/// When the current local day started, in seconds since the Unix epoch
+pub fn start_of_day() -> u64 {
+	let mut tm = local_time(now());
+	tm.tm_hour = 0;
+	tm.tm_min = 0;
+	tm.tm_sec = 0;
+
+	from_local_time(tm)
+}
+
+/// When the current local week started on Monday, in seconds since the Unix epoch
+pub fn start_of_week() -> u64 {
+	let mut tm = local_time(start_of_day());
+	// tm_wday counts from Sunday
+	tm.tm_mday -= (tm.tm_wday + 6) % 7;
+
+	from_local_time(tm)
+}
+
+/// Formats a Unix timestamp as a local date and time, e.g. "2024-03-01 18:04"
+pub fn format_date(timestamp: u64) -> String {
+	let tm = local_time(timestamp);
+
+	format!("{}-{:02}-{:02} {:02}:{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min)
+}
+
+/// Adds every finished session to the play-time history
+pub struct Recorder;
+
+impl SessionHook for Recorder {
+	fn on_exit(&mut self, session: &Session) {
+		let played = session.played();
+
+		if played.as_secs() == 0 {
+			return;
+		}
+
+		let mut playtime = Playtime::load();
+		playtime.sessions.push(PlayedSession {
+			alias: session.alias.clone(),
+			id: session.id,
+			start: now() - played.as_secs(),
+			seconds: played.as_secs()
+		});
+		playtime.save();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn session(start: u64, seconds: u64) -> PlayedSession {
+		PlayedSession { alias: Some("game".to_string()), id: 10, start, seconds }
+	}
+
+	#[test]
+	fn clips_sessions_to_the_period() {
+		let since = now() - 3600;
+		let playtime = Playtime {
+			sessions: vec![
+				// Started half an hour before the period, ended 10 minutes into it
+				session(since - 1800, 2400),
+				session(since + 60, 120),
+				session(since - 7200, 600)
+			]
+		};
+
+		assert_eq!(playtime.played_since(since, Some("game")), Duration::from_secs(600 + 120));
+		assert_eq!(playtime.played_since(since, Some("other")), Duration::from_secs(0));
+	}
+
+	#[test]
+	fn finds_the_start_of_the_local_day_and_week() {
+		let day = start_of_day();
+		let week = start_of_week();
+
+		assert!(day <= now() && now() - day < 25 * 3600);
+		assert!(format_date(day).ends_with(" 00:00"));
+		assert!(week <= day && day - week < 7 * DAY + 3600);
+		assert_eq!(local_time(week).tm_wday, 1);
+		assert!(format_date(week).ends_with(" 00:00"));
+	}
+}
@@ -0,0 +1,215 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::session::{Session, SessionHook};
+
+/// Where the CPUs and their frequency settings are found
+const CPU_DIR: &str = "/sys/devices/system/cpu";
+
+/// Runs a command, returning its trimmed output if it succeeded
+fn output(program: &str, args: &[&str]) -> Option<String> {
+	let output = Command::new(program)
+		.args(args)
+		.stderr(Stdio::null())
+		.output()
+		.ok()
+		.filter(|output| output.status.success())?;
+
+	Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A daemon that manages power profiles
+#[derive(Clone, Copy)]
+enum Daemon {
+	/// power-profiles-daemon, with e.g. 'performance', 'balanced' and 'power-saver'
+	PowerProfiles,
+	/// tuned, with e.g. 'throughput-performance' and 'balanced'
+	Tuned
+}
+
+impl Daemon {
+	fn name(self) -> &'static str {
+		match self {
+			Daemon::PowerProfiles => "power-profiles-daemon",
+			Daemon::Tuned => "tuned"
+		}
+	}
+
+	fn active_profile(self) -> Option<String> {
+		match self {
+			Daemon::PowerProfiles => output("powerprofilesctl", &["get"]),
+			// Prints "Current active profile: <profile>"
+			Daemon::Tuned => output("tuned-adm", &["active"])?
+				.rsplit(": ")
+				.next()
+				.map(String::from)
+		}
+	}
+
+	fn set_profile(self, profile: &str) -> bool {
+		match self {
+			Daemon::PowerProfiles => output("powerprofilesctl", &["set", profile]),
+			Daemon::Tuned => output("tuned-adm", &["profile", profile])
+		}.is_some()
+	}
+}
+
+/// Finds the running power profile daemon along with its active profile
+fn find_daemon() -> Option<(Daemon, String)> {
+	[Daemon::PowerProfiles, Daemon::Tuned].iter()
+		.find_map(|daemon| daemon.active_profile().map(|profile| (*daemon, profile)))
+}
+
+/// Switches to a power profile while a game runs and switches back to the
+/// previous one afterwards
+pub struct PowerProfileHook {
+	profile: String,
+	changed: Option<(Daemon, String)>
+}
+
+impl PowerProfileHook {
+	pub fn new(profile: &str) -> PowerProfileHook {
+		PowerProfileHook { profile: profile.to_string(), changed: None }
+	}
+}
+
+impl SessionHook for PowerProfileHook {
+	fn on_launch(&mut self, _session: &Session) {
+		match find_daemon() {
+			Some((daemon, previous)) if previous == self.profile => {
+				println!("Power profile is already '{}' ({})", previous, daemon.name());
+			},
+			Some((daemon, previous)) => {
+				if daemon.set_profile(&self.profile) {
+					println!("Switched power profile from '{}' to '{}' ({})", previous, self.profile, daemon.name());
+					self.changed = Some((daemon, previous));
+				} else {
+					println!("Could not switch to power profile '{}' ({})", self.profile, daemon.name());
+				}
+			},
+			None => println!("Could not find a power profile daemon (power-profiles-daemon or tuned)")
+		}
+	}
+
+	fn on_exit(&mut self, _session: &Session) {
+		if let Some((daemon, previous)) = self.changed.take() {
+			if daemon.set_profile(&previous) {
+				println!("Switched power profile back to '{}' ({})", previous, daemon.name());
+			} else {
+				println!("Could not switch back to power profile '{}' ({})", previous, daemon.name());
+			}
+		}
+	}
+}
+
+/// Lists the CPUs that have a frequency governor, with their governor files
+fn governor_files() -> Vec<(u32, PathBuf)> {
+	let mut files: Vec<(u32, PathBuf)> = fs::read_dir(CPU_DIR)
+		.map(|entries| entries.flatten()
+			.filter_map(|entry| {
+				let cpu = entry.file_name().to_str()?.strip_prefix("cpu")?.parse().ok()?;
+				let file = entry.path().join("cpufreq/scaling_governor");
+
+				Some((cpu, file)).filter(|(_, file)| file.exists())
+			})
+			.collect())
+		.unwrap_or_default();
+
+	files.sort();
+	files
+}
+
+/// Reads the current governor of every CPU
+fn read_governors() -> Vec<(u32, String)> {
+	governor_files().into_iter()
+		.filter_map(|(cpu, file)| fs::read_to_string(file).ok().map(|governor| (cpu, governor.trim().to_string())))
+		.collect()
+}
+
+/// Sets the governor of some CPUs, writing to sysfs directly if allowed and
+/// otherwise through cpupower with sudo (without a password) or polkit
+fn set_governor(cpus: &[u32], governor: &str) -> bool {
+	let files = governor_files();
+	let written = cpus.iter().all(|cpu| {
+		files.iter()
+			.find(|(other, _)| other == cpu)
+			.is_some_and(|(_, file)| fs::write(file, governor).is_ok())
+	});
+
+	if written {
+		return true;
+	}
+
+	let list = cpus.iter().map(|cpu| cpu.to_string()).collect::<Vec<String>>().join(",");
+	let args = ["cpupower", "-c", &list, "frequency-set", "-g", governor];
+
+	[&["sudo", "-n"][..], &["pkexec"][..]].iter().any(|prefix| {
+		Command::new(prefix[0])
+			.args(&prefix[1..])
+			.args(args)
+			.stdout(Stdio::null())
+			.stderr(Stdio::null())
+			.status()
+			.map(|status| status.success())
+			.unwrap_or(false)
+	})
+}
+
+/// Switches the CPU frequency governor while a game runs and restores each
+/// CPU's previous governor afterwards
+pub struct GovernorHook {
+	governor: String,
+	previous: Vec<(u32, String)>
+}
+
+impl GovernorHook {
+	pub fn new(governor: &str) -> GovernorHook {
+		GovernorHook { governor: governor.to_string(), previous: Vec::new() }
+	}
+}
+
+impl SessionHook for GovernorHook {
+	fn on_launch(&mut self, _session: &Session) {
+		let previous = read_governors();
+
+		if previous.is_empty() {
+			println!("Could not find any CPU frequency governors in {}", CPU_DIR);
+			return;
+		}
+
+		let cpus: Vec<u32> = previous.iter()
+			.filter(|(_, governor)| *governor != self.governor)
+			.map(|(cpu, _)| *cpu)
+			.collect();
+
+		if cpus.is_empty() {
+			println!("CPU governor is already '{}'", self.governor);
+		} else if set_governor(&cpus, &self.governor) {
+			println!("Switched CPU governor to '{}'", self.governor);
+			self.previous = previous.into_iter().filter(|(cpu, _)| cpus.contains(cpu)).collect();
+		} else {
+			println!("Could not switch CPU governor to '{}'; is cpupower installed, or sudo or polkit allowed to run it?", self.governor);
+		}
+	}
+
+	fn on_exit(&mut self, _session: &Session) {
+		let mut previous = std::mem::take(&mut self.previous);
+
+		// CPUs that had the same governor are switched back together
+		while let Some((_, governor)) = previous.first().cloned() {
+			let cpus: Vec<u32> = previous.iter()
+				.filter(|(_, other)| *other == governor)
+				.map(|(cpu, _)| *cpu)
+				.collect();
+
+			if set_governor(&cpus, &governor) {
+				println!("Switched CPU governor back to '{}'", governor);
+			} else {
+				println!("Could not switch CPU governor back to '{}'", governor);
+			}
+
+			previous.retain(|(_, other)| *other != governor);
+		}
+	}
+}
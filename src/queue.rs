@@ -0,0 +1,35 @@
+use std::fs;
+use std::path::PathBuf;
+
+use crate::data;
+
+static QUEUE_FILE_NAME: &str = "queue.json";
+
+/// Aliases waiting to be played one after another
+pub struct Queue {
+	pub aliases: Vec<String>
+}
+
+impl Queue {
+	pub fn path() -> PathBuf {
+		data::data_dir().join(QUEUE_FILE_NAME)
+	}
+
+	pub fn load() -> Queue {
+		let aliases = fs::read_to_string(Queue::path())
+			.ok()
+			.and_then(|text| serde_json::from_str(&text).ok())
+			.unwrap_or_default();
+
+		Queue { aliases }
+	}
+
+	pub fn save(&self, message: String) {
+		match fs::write(Queue::path(), serde_json::to_string_pretty(&self.aliases).unwrap()) {
+			Ok(_) => if !message.is_empty() {
+				println!("{}", message);
+			},
+			Err(_) => println!("Error while writing to {}", QUEUE_FILE_NAME)
+		}
+	}
+}
@@ -0,0 +1,366 @@
+use std::fs;
+use std::os::unix::io::AsRawFd;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for a launched game to show up (e.g. while Steam updates it)
+pub const START_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// How often running processes are checked
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// How long a game gets to exit on its own before it is killed
+const TERMINATE_GRACE_PERIOD: Duration = Duration::from_secs(10);
+
+/// The signals that stop waiting for a session: Ctrl-C, the terminal being
+/// closed and e.g. a logout asking steamlet to exit
+const INTERRUPT_SIGNALS: [libc::c_int; 3] = [libc::SIGINT, libc::SIGHUP, libc::SIGTERM];
+
+/// Set when one of the interrupt signals arrives while a session is waited for
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Set when the terminal was closed, after which nothing can be printed to it
+static HUNG_UP: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn interrupt(signal: libc::c_int) {
+	if signal == libc::SIGHUP {
+		HUNG_UP.store(true, Ordering::SeqCst);
+	}
+
+	INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Catches the interrupt signals for as long as it lives, so that a session
+/// can stop waiting and still undo what its hooks changed
+pub struct InterruptGuard {
+	previous: Vec<(libc::c_int, libc::sighandler_t)>
+}
+
+impl InterruptGuard {
+	pub fn new() -> InterruptGuard {
+		INTERRUPTED.store(false, Ordering::SeqCst);
+
+		let handler: extern "C" fn(libc::c_int) = interrupt;
+		let previous = INTERRUPT_SIGNALS.iter()
+			.map(|signal| (*signal, unsafe { libc::signal(*signal, handler as libc::sighandler_t) }))
+			.collect();
+
+		InterruptGuard { previous }
+	}
+
+	pub fn interrupted(&self) -> bool {
+		// Output goes nowhere once the terminal is gone, rather than making
+		// println panic before the hooks could clean up
+		if HUNG_UP.swap(false, Ordering::SeqCst) {
+			if let Ok(null) = fs::OpenOptions::new().write(true).open("/dev/null") {
+				unsafe {
+					libc::dup2(null.as_raw_fd(), libc::STDOUT_FILENO);
+					libc::dup2(null.as_raw_fd(), libc::STDERR_FILENO);
+				}
+			}
+		}
+
+		INTERRUPTED.load(Ordering::SeqCst)
+	}
+}
+
+impl Drop for InterruptGuard {
+	fn drop(&mut self) {
+		for (signal, previous) in &self.previous {
+			unsafe {
+				libc::signal(*signal, *previous);
+			}
+		}
+	}
+}
+
+/// Lists the PIDs of running processes that belong to a Steam app
+///
+/// Steam starts games through its 'reaper' process with an 'AppId=<id>'
+/// argument and sets 'SteamAppId' in the environment of the game itself
+pub fn find_game_processes(id: u32) -> Vec<u32> {
+	let arg = format!("AppId={}", id);
+	let env = format!("SteamAppId={}", id);
+	let mut pids = Vec::new();
+
+	let entries = match fs::read_dir("/proc") {
+		Ok(entries) => entries,
+		Err(_) => return pids
+	};
+
+	for entry in entries.flatten() {
+		let pid: u32 = match entry.file_name().to_str().and_then(|s| s.parse().ok()) {
+			Some(pid) => pid,
+			None => continue
+		};
+
+		let matches = |file: &str, needle: &str| {
+			fs::read(entry.path().join(file))
+				.map(|bytes| bytes.split(|b| *b == 0).any(|part| part == needle.as_bytes()))
+				.unwrap_or(false)
+		};
+
+		if matches("cmdline", &arg) || matches("environ", &env) {
+			pids.push(pid);
+		}
+	}
+
+	pids
+}
+
+/// Lists the IDs of the Steam apps that are running, from the 'SteamAppId'
+/// in the environment of their processes
+pub fn running_games() -> Vec<u32> {
+	let mut ids = Vec::new();
+
+	let entries = match fs::read_dir("/proc") {
+		Ok(entries) => entries,
+		Err(_) => return ids
+	};
+
+	for entry in entries.flatten() {
+		let environ = match fs::read(entry.path().join("environ")) {
+			Ok(environ) => environ,
+			Err(_) => continue
+		};
+
+		let id = environ.split(|b| *b == 0)
+			.find_map(|var| var.strip_prefix(b"SteamAppId="))
+			.and_then(|id| std::str::from_utf8(id).ok()?.parse::<u32>().ok())
+			.filter(|id| *id != 0);
+
+		if let Some(id) = id.filter(|id| !ids.contains(id)) {
+			ids.push(id);
+		}
+	}
+
+	ids
+}
+
+/// Formats a duration as e.g. "1h 05m" or "3m 20s"
+pub fn format_duration(duration: Duration) -> String {
+	let secs = duration.as_secs();
+
+	if secs >= 3600 {
+		format!("{}h {:02}m", secs / 3600, (secs % 3600) / 60)
+	} else {
+		format!("{}m {:02}s", secs / 60, secs % 60)
+	}
+}
+
+/// A launched game that is waited for until it exits
+pub struct Session {
+	pub id: u32,
+	pub name: String,
+	/// The alias the game was played with, if any
+	pub alias: Option<String>,
+	/// Set when a hook had the game closed
+	pub closed: bool,
+	/// Set when waiting was stopped with Ctrl-C (or a hangup or SIGTERM), while
+	/// the game kept running
+	pub interrupted: bool,
+	/// The game's processes that were running at the last refresh
+	pub running: Vec<u32>,
+	/// Every process of the game that started after the session was created,
+	/// which are the only ones that are ever signalled
+	pub pids: Vec<u32>,
+	/// Processes of the app that were already running before the launch
+	existing: Vec<u32>,
+	started: Option<Instant>
+}
+
+impl Session {
+	/// Creates a session for a game that is about to be launched
+	pub fn new(id: u32, name: String, alias: Option<String>) -> Session {
+		let existing = find_game_processes(id);
+
+		Session { id, name, alias, closed: false, interrupted: false, running: Vec::new(), pids: Vec::new(), existing, started: None }
+	}
+
+	/// Looks for the game's processes, returning whether any are running
+	pub fn refresh(&mut self) -> bool {
+		self.running = find_game_processes(self.id);
+
+		for pid in &self.running {
+			if !self.existing.contains(pid) && !self.pids.contains(pid) {
+				self.pids.push(*pid);
+			}
+		}
+
+		if self.started.is_none() && !self.running.is_empty() {
+			self.started = Some(Instant::now());
+		}
+
+		!self.running.is_empty()
+	}
+
+	/// Whether the game's processes have shown up since it was launched
+	pub fn has_started(&self) -> bool {
+		self.started.is_some()
+	}
+
+	/// How long the game has been running for
+	pub fn played(&self) -> Duration {
+		self.started.map(|started| started.elapsed()).unwrap_or_default()
+	}
+}
+
+/// Takes part in a session, e.g. to enforce a time limit or to change system
+/// settings for the duration of the game
+pub trait SessionHook {
+	/// Called after the game is launched, before its processes show up
+	fn on_launch(&mut self, _session: &Session) {}
+
+	/// Called regularly while the game runs; returning false closes the game
+	fn on_tick(&mut self, _session: &Session) -> bool {
+		true
+	}
+
+	/// Called when waiting is stopped with Ctrl-C; returning false closes the
+	/// game instead of leaving it running
+	fn allows_interrupt(&mut self, _session: &Session) -> bool {
+		true
+	}
+
+	/// Called once the game has exited, or didn't start at all
+	fn on_exit(&mut self, _session: &Session) {}
+}
+
+/// Waits for a launched game to start and then exit, returning how long it
+/// ran, or None if it didn't start or waiting was interrupted
+///
+/// Pressing Ctrl-C stops waiting without closing the game, unless a hook
+/// wants it closed instead; the hooks still get their 'on_exit' call
+pub fn run(mut session: Session, hooks: &mut [Box<dyn SessionHook>]) -> Option<Duration> {
+	let guard = InterruptGuard::new();
+
+	for hook in hooks.iter_mut() {
+		hook.on_launch(&session);
+	}
+
+	println!("Waiting for {} to start...", session.name);
+
+	let launched = Instant::now();
+	// Set once waiting was interrupted but a hook wants the game closed
+	let mut close = false;
+
+	while !session.refresh() && launched.elapsed() <= START_TIMEOUT {
+		if guard.interrupted() && !close {
+			if allows_interrupt(hooks, &session) {
+				session.interrupted = true;
+				break;
+			}
+
+			println!("Waiting for {} to start so it can be closed...", session.name);
+			close = true;
+		}
+
+		thread::sleep(POLL_INTERVAL);
+	}
+
+	if !session.has_started() {
+		if session.interrupted {
+			println!("Stopped waiting for {}", session.name);
+		} else {
+			println!("{} did not start within {}", session.name, format_duration(START_TIMEOUT));
+		}
+
+		for hook in hooks.iter_mut() {
+			hook.on_exit(&session);
+		}
+
+		return None;
+	}
+
+	while session.refresh() {
+		if guard.interrupted() && !close {
+			if allows_interrupt(hooks, &session) {
+				println!("Stopped waiting for {}", session.name);
+				session.interrupted = true;
+				break;
+			}
+
+			close = true;
+		}
+
+		// Every hook gets its tick, even if an earlier one wants the game closed
+		let ticks: Vec<bool> = hooks.iter_mut().map(|hook| hook.on_tick(&session)).collect();
+
+		if close || ticks.contains(&false) {
+			println!("Closing {}", session.name);
+			terminate(&mut session);
+			session.closed = true;
+			break;
+		}
+
+		thread::sleep(POLL_INTERVAL);
+	}
+
+	let played = session.played();
+
+	if !session.interrupted {
+		println!("{} exited after {}", session.name, format_duration(played));
+	}
+
+	for hook in hooks.iter_mut() {
+		hook.on_exit(&session);
+	}
+
+	// The game may still be running
+	if session.interrupted {
+		return None;
+	}
+
+	Some(played)
+}
+
+/// Asks every hook whether the game may keep running once waiting is interrupted
+fn allows_interrupt(hooks: &mut [Box<dyn SessionHook>], session: &Session) -> bool {
+	let answers: Vec<bool> = hooks.iter_mut().map(|hook| hook.allows_interrupt(session)).collect();
+
+	!answers.contains(&false)
+}
+
+/// Asks the processes of a session's game to exit, killing those that are
+/// still running after a grace period
+///
+/// Only processes that started during the session are signalled, and only
+/// while they still belong to the game
+pub fn terminate(session: &mut Session) {
+	let signal = |signal: &str, pids: &[u32]| {
+		Command::new("kill")
+			.arg(signal)
+			.args(pids.iter().map(|pid| pid.to_string()))
+			.stderr(Stdio::null())
+			.status()
+			.map(drop)
+			.unwrap_or(());
+	};
+
+	let own = |session: &mut Session| -> Vec<u32> {
+		session.refresh();
+		session.running.iter().filter(|pid| session.pids.contains(pid)).copied().collect()
+	};
+
+	let pids = own(session);
+
+	if pids.is_empty() {
+		return;
+	}
+
+	signal("-TERM", &pids);
+
+	let asked = Instant::now();
+
+	while asked.elapsed() < TERMINATE_GRACE_PERIOD {
+		thread::sleep(POLL_INTERVAL);
+
+		if own(session).is_empty() {
+			return;
+		}
+	}
+
+	signal("-KILL", &own(session));
+}
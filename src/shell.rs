@@ -0,0 +1,508 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use structopt::StructOpt;
+
+use crate::config::Config;
+use crate::data::{self, AliasData};
+use crate::Steamlet;
+
+static HISTORY_FILE_NAME: &str = "shell_history";
+static PROMPT: &str = "steamlet> ";
+
+/// How many lines of history are kept between shells
+const HISTORY_SIZE: usize = 500;
+
+/// The commands completed with <Tab>, with their own subcommands, except
+/// 'shell' itself; clap 2 has no public way to list them
+static COMMANDS: &[(&str, &[&str])] = &[
+	("breaks", &[]),
+	("bugreport", &[]),
+	("cards", &[]),
+	("config", &[]),
+	("defaults", &[]),
+	("help", &[]),
+	("hub", &[]),
+	("idle", &[]),
+	("install", &[]),
+	("limit", &[]),
+	("list", &[]),
+	("manifest", &[]),
+	("opts", &["clear", "import-steam", "push-steam", "set", "show"]),
+	("play", &[]),
+	("queue", &["add", "clear", "list", "play", "remove"]),
+	("remove", &[]),
+	("set", &[]),
+	("top", &[]),
+	("url", &[]),
+	("watch", &["check", "list"]),
+	("whoami", &[]),
+	("wishlist", &["import"])
+];
+
+/// Runs an interactive prompt that executes steamlet commands against the
+/// already loaded data and config, in plain mode if the shell was started in it
+pub fn run(data: &mut AliasData, config: &mut Config, plain: bool, execute: fn(Steamlet, &mut AliasData, &mut Config)) {
+	let history_path = data::data_dir().join(HISTORY_FILE_NAME);
+	let mut history: Vec<String> = fs::read_to_string(&history_path)
+		.map(|text| text.lines().map(String::from).collect())
+		.unwrap_or_default();
+
+	let terminal = Terminal::new();
+
+	if terminal.is_some() {
+		println!("Type a command without 'steamlet', 'help' for a list of commands or 'exit' to quit; <Tab> completes commands and aliases");
+	}
+
+	loop {
+		let line = match &terminal {
+			Some(terminal) => terminal.read_line(&history, data),
+			None => read_plain_line()
+		};
+
+		let line = match line {
+			Some(line) => line,
+			None => break
+		};
+
+		let words = split_words(&line);
+
+		match words.first().map(String::as_str) {
+			None => continue,
+			Some("exit") | Some("quit") => break,
+			Some("shell") => {
+				println!("Already in the shell");
+				continue;
+			},
+			_ => {}
+		}
+
+		if history.last() != Some(&line) {
+			history.push(line.clone());
+		}
+
+		let args: Vec<String> = std::iter::once("steamlet".to_string())
+			.chain(Some("--plain".to_string()).filter(|_| plain))
+			.chain(words)
+			.collect();
+		let args = crate::with_defaults(config, args);
+
+		// Parsing errors and '--help' are shown without leaving the shell
+		match crate::app(config, &args).get_matches_from_safe(&args) {
+			Ok(matches) => execute(Steamlet::from_clap(&matches), data, config),
+			Err(e) => println!("{}", e.message)
+		}
+	}
+
+	let start = history.len().saturating_sub(HISTORY_SIZE);
+	fs::write(&history_path, history[start..].join("\n")).unwrap_or(());
+}
+
+fn read_plain_line() -> Option<String> {
+	let mut line = String::new();
+
+	match io::stdin().read_line(&mut line) {
+		Ok(0) | Err(_) => None,
+		Ok(_) => Some(line.trim_end().to_string())
+	}
+}
+
+/// Splits a line into words like a shell does, honouring quotes and
+/// backslash escapes
+pub fn split_words(line: &str) -> Vec<String> {
+	let mut words = Vec::new();
+	let mut word = String::new();
+	let mut in_word = false;
+	let mut quote: Option<char> = None;
+	let mut chars = line.chars();
+
+	while let Some(c) = chars.next() {
+		match (c, quote) {
+			('\\', q) if q != Some('\'') => {
+				if let Some(next) = chars.next() {
+					word.push(next);
+				}
+				in_word = true;
+			},
+			(c, Some(q)) if c == q => quote = None,
+			(c, Some(_)) => word.push(c),
+			('"', None) | ('\'', None) => {
+				quote = Some(c);
+				in_word = true;
+			},
+			(c, None) if c.is_whitespace() => {
+				if in_word {
+					words.push(std::mem::take(&mut word));
+					in_word = false;
+				}
+			},
+			(c, None) => {
+				word.push(c);
+				in_word = true;
+			}
+		}
+	}
+
+	if in_word {
+		words.push(word);
+	}
+
+	words
+}
+
+/// Lists the possible completions of the last word of a line
+fn completions(words: &[String], data: &AliasData) -> Vec<String> {
+	let word = words.last().map(String::as_str).unwrap_or("");
+
+	let mut candidates: Vec<String> = match words.len() {
+		0 | 1 => COMMANDS.iter()
+			.map(|(name, _)| name.to_string())
+			.chain(vec!["exit".to_string()])
+			.collect(),
+		n => {
+			let nested = COMMANDS.iter()
+				.find(|(name, _)| *name == words[0])
+				.map(|(_, nested)| *nested);
+
+			match nested {
+				Some(nested) if n == 2 && !nested.is_empty() => nested.iter()
+					.map(|name| name.to_string())
+					.collect(),
+				_ => data.aliases.keys().cloned().collect()
+			}
+		}
+	};
+
+	candidates.retain(|c| c.starts_with(word));
+	candidates.sort();
+	candidates.dedup();
+	candidates
+}
+
+/// Quotes a word for the shell if it contains spaces or quotes
+fn quote(word: &str) -> String {
+	if word.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+		format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+	} else {
+		word.to_string()
+	}
+}
+
+/// A terminal switched into non-canonical mode while a line is read
+struct Terminal {
+	saved: String
+}
+
+impl Terminal {
+	/// Returns None if stdin isn't a terminal
+	fn new() -> Option<Terminal> {
+		let output = Command::new("stty")
+			.arg("-g")
+			.stdin(Stdio::inherit())
+			.stderr(Stdio::null())
+			.output()
+			.ok()?;
+
+		if !output.status.success() {
+			return None;
+		}
+
+		Some(Terminal { saved: String::from_utf8_lossy(&output.stdout).trim().to_string() })
+	}
+
+	fn stty(&self, args: &[&str]) {
+		Command::new("stty")
+			.args(args)
+			.stdin(Stdio::inherit())
+			.status()
+			.map(drop)
+			.unwrap_or(());
+	}
+
+	fn read_line(&self, history: &[String], data: &AliasData) -> Option<String> {
+		self.stty(&["-icanon", "-echo", "-isig", "min", "1"]);
+		let line = LineEditor::new(history).run(data);
+		self.stty(&[&self.saved]);
+
+		line
+	}
+}
+
+struct LineEditor<'a> {
+	history: &'a [String],
+	/// The position in history, equal to its length for the line being typed
+	history_pos: usize,
+	buffer: Vec<char>,
+	typed: Vec<char>,
+	cursor: usize
+}
+
+impl<'a> LineEditor<'a> {
+	fn new(history: &'a [String]) -> LineEditor<'a> {
+		LineEditor {
+			history,
+			history_pos: history.len(),
+			buffer: Vec::new(),
+			typed: Vec::new(),
+			cursor: 0
+		}
+	}
+
+	fn run(&mut self, data: &AliasData) -> Option<String> {
+		self.redraw();
+
+		loop {
+			let c = read_char()?;
+
+			match c {
+				'\r' | '\n' => {
+					println!();
+					return Some(self.buffer.iter().collect());
+				},
+				// Ctrl-D quits on an empty line
+				'\u{4}' if self.buffer.is_empty() => {
+					println!();
+					return None;
+				},
+				// Ctrl-C discards the line
+				'\u{3}' => {
+					println!("^C");
+					self.buffer.clear();
+					self.cursor = 0;
+				},
+				'\u{7f}' | '\u{8}' if self.cursor > 0 => {
+					self.cursor -= 1;
+					self.buffer.remove(self.cursor);
+				},
+				'\t' => self.complete(data),
+				'\u{1b}' => self.escape_sequence(),
+				c if !c.is_control() => {
+					self.buffer.insert(self.cursor, c);
+					self.cursor += 1;
+				},
+				_ => {}
+			}
+
+			self.redraw();
+		}
+	}
+
+	fn escape_sequence(&mut self) {
+		if read_char() != Some('[') {
+			return;
+		}
+
+		match read_char() {
+			Some('A') => self.browse_history(-1),
+			Some('B') => self.browse_history(1),
+			Some('C') if self.cursor < self.buffer.len() => self.cursor += 1,
+			Some('D') if self.cursor > 0 => self.cursor -= 1,
+			Some('H') => self.cursor = 0,
+			Some('F') => self.cursor = self.buffer.len(),
+			Some('3') if read_char() == Some('~') && self.cursor < self.buffer.len() => {
+				self.buffer.remove(self.cursor);
+			},
+			_ => {}
+		}
+	}
+
+	fn browse_history(&mut self, step: isize) {
+		let pos = self.history_pos as isize + step;
+
+		if pos < 0 || pos > self.history.len() as isize {
+			return;
+		}
+
+		if self.history_pos == self.history.len() {
+			self.typed = self.buffer.clone();
+		}
+
+		self.history_pos = pos as usize;
+		self.buffer = match self.history.get(self.history_pos) {
+			Some(line) => line.chars().collect(),
+			None => self.typed.clone()
+		};
+		self.cursor = self.buffer.len();
+	}
+
+	fn complete(&mut self, data: &AliasData) {
+		let before: String = self.buffer[..self.cursor].iter().collect();
+		let mut words = split_words(&before);
+
+		// A trailing space means a new word is being started
+		if before.is_empty() || before.ends_with(char::is_whitespace) {
+			words.push(String::new());
+		}
+
+		let candidates = completions(&words, data);
+		let word = words.last().unwrap();
+
+		let replacement = match candidates.as_slice() {
+			[] => return,
+			[only] => format!("{} ", quote(only)),
+			_ => {
+				let prefix = common_prefix(&candidates);
+
+				if prefix.chars().count() <= word.chars().count() {
+					println!();
+					println!("{}", candidates.iter().map(|c| quote(c)).collect::<Vec<_>>().join("  "));
+					return;
+				}
+
+				quote(&prefix).trim_end_matches('"').to_string()
+			}
+		};
+
+		// Replace the word being completed, including any quote it began with
+		let start = word_start(&before);
+		self.buffer.splice(start..self.cursor, replacement.chars());
+		self.cursor = start + replacement.chars().count();
+	}
+
+	fn redraw(&self) {
+		let line: String = self.buffer.iter().collect();
+		let back = self.buffer.len() - self.cursor;
+
+		print!("\r\x1b[K{}{}", PROMPT, line);
+
+		if back > 0 {
+			print!("\x1b[{}D", back);
+		}
+
+		io::stdout().flush().unwrap();
+	}
+}
+
+/// Finds the char index where the last word of a line starts
+fn word_start(line: &str) -> usize {
+	let chars: Vec<char> = line.chars().collect();
+	let mut start = 0;
+	let mut quote: Option<char> = None;
+
+	for (i, c) in chars.iter().enumerate() {
+		match (*c, quote) {
+			(c, Some(q)) if c == q => quote = None,
+			(_, Some(_)) => {},
+			('"', None) | ('\'', None) => quote = Some(*c),
+			(c, None) if c.is_whitespace() => start = i + 1,
+			_ => {}
+		}
+	}
+
+	start
+}
+
+fn common_prefix(words: &[String]) -> String {
+	let first: Vec<char> = words[0].chars().collect();
+	let len = words.iter()
+		.map(|w| w.chars().zip(&first).take_while(|(a, b)| a == *b).count())
+		.min()
+		.unwrap_or(0);
+
+	first[..len].iter().collect()
+}
+
+/// The length of a UTF-8 character by its first byte, or None if the byte
+/// can't start one
+fn char_len(lead: u8) -> Option<usize> {
+	match lead {
+		0x00..=0x7f => Some(1),
+		0xc2..=0xdf => Some(2),
+		0xe0..=0xef => Some(3),
+		0xf0..=0xf4 => Some(4),
+		_ => None
+	}
+}
+
+/// Reads one UTF-8 character from stdin, skipping invalid bytes
+fn read_char() -> Option<char> {
+	let mut stdin = io::stdin();
+	let mut bytes = [0u8; 4];
+
+	loop {
+		stdin.read_exact(&mut bytes[..1]).ok()?;
+
+		let len = match char_len(bytes[0]) {
+			Some(len) => len,
+			None => continue
+		};
+
+		stdin.read_exact(&mut bytes[1..len]).ok()?;
+
+		if let Ok(s) = std::str::from_utf8(&bytes[..len]) {
+			return s.chars().next();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn splits_words_like_a_shell() {
+		assert_eq!(split_words("  play  ets2 "), vec!["play", "ets2"]);
+		assert_eq!(split_words(r#"set "euro truck" 227300"#), vec!["set", "euro truck", "227300"]);
+		assert_eq!(split_words(r#"a 'b "c"' d\ e "f\"g" ''"#), vec!["a", "b \"c\"", "d e", "f\"g", ""]);
+		assert_eq!(split_words(r"'no\escape'"), vec![r"no\escape"]);
+		assert!(split_words("   ").is_empty());
+	}
+
+	#[test]
+	fn finds_the_start_of_the_last_word() {
+		assert_eq!(word_start(""), 0);
+		assert_eq!(word_start("play et"), 5);
+		assert_eq!(word_start("play "), 5);
+		assert_eq!(word_start(r#"play "euro tr"#), 5);
+		assert_eq!(word_start("défauts ü"), 8);
+	}
+
+	#[test]
+	fn finds_the_common_prefix() {
+		let words = |list: &[&str]| list.iter().map(|w| w.to_string()).collect::<Vec<_>>();
+
+		assert_eq!(common_prefix(&words(&["queue", "quit"])), "qu");
+		assert_eq!(common_prefix(&words(&["ärger", "ärgern"])), "ärger");
+		assert_eq!(common_prefix(&words(&["play", "list"])), "");
+		assert_eq!(common_prefix(&words(&["only"])), "only");
+	}
+
+	#[test]
+	fn rejects_invalid_lead_bytes() {
+		assert_eq!(char_len(b'a'), Some(1));
+		assert_eq!(char_len("é".as_bytes()[0]), Some(2));
+		assert_eq!(char_len("ゲ".as_bytes()[0]), Some(3));
+		assert_eq!(char_len("🎮".as_bytes()[0]), Some(4));
+
+		for lead in [0x80, 0xbf, 0xc0, 0xc1, 0xf5, 0xff] {
+			assert_eq!(char_len(lead), None);
+		}
+	}
+
+	#[test]
+	fn completes_every_command() {
+		let mut help = Vec::new();
+		Steamlet::clap().write_help(&mut help).unwrap();
+		let help = String::from_utf8(help).unwrap();
+
+		// Command names are indented by four spaces, wrapped descriptions by more
+		let listed: Vec<&str> = help.lines()
+			.skip_while(|line| *line != "SUBCOMMANDS:")
+			.skip(1)
+			.take_while(|line| !line.is_empty())
+			.filter(|line| line.len() - line.trim_start().len() == 4)
+			.filter_map(|line| line.split_whitespace().next())
+			.collect();
+		// 'help' is added by clap when parsing; 'shell' can't be run in the shell
+		let names: Vec<&str> = COMMANDS.iter().map(|(name, _)| *name).filter(|name| *name != "help").collect();
+
+		assert_eq!(listed.into_iter().filter(|name| *name != "shell").collect::<Vec<_>>(), names);
+
+		for (name, nested) in COMMANDS {
+			for sub in nested.iter() {
+				let result = Steamlet::clap().get_matches_from_safe(vec!["steamlet", name, sub, "--help"]);
+				assert_eq!(result.map_err(|e| e.kind).err(), Some(structopt::clap::ErrorKind::HelpDisplayed), "{} {}", name, sub);
+			}
+		}
+	}
+}
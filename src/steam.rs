@@ -0,0 +1,233 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::Config;
+use crate::store::AppType;
+use steamlet::binary_vdf;
+use steamlet::vdf::{self, Vdf};
+
+// The 'steam' command becomes the client itself if it isn't running yet, so
+// it is intentionally left running in the background
+#[allow(clippy::zombie_processes)]
+pub fn open_steam_url(url: String) {
+	Command::new("steam")
+		.arg(url)
+		.spawn()
+		.expect("'steam' command failed to start");
+}
+
+/// Percent-encodes text for a part of a 'steam://' URL, e.g. launch options
+pub fn url_encode(text: &str) -> String {
+	text.bytes()
+		.map(|b| match b {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+			_ => format!("%{:02X}", b)
+		})
+		.collect()
+}
+
+/// Finds the root directory of the Steam installation, which can be
+/// overridden with the 'steam_dir' config key
+pub fn steam_root(config: &Config) -> Option<PathBuf> {
+	if let Some(dir) = config.get_str("steam_dir") {
+		return Some(PathBuf::from(dir));
+	}
+
+	let home = dirs::home_dir()?;
+
+	[
+		home.join(".steam/steam"),
+		home.join(".local/share/Steam"),
+		home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"),
+		home.join("Library/Application Support/Steam")
+	]
+		.iter()
+		.find(|dir| dir.join("steamapps").is_dir())
+		.cloned()
+}
+
+/// Lists the 'steamapps' directories of every Steam library
+pub fn library_dirs(config: &Config) -> Vec<PathBuf> {
+	let root = match steam_root(config) {
+		Some(root) => root,
+		None => return Vec::new()
+	};
+
+	let mut dirs = vec![root.join("steamapps")];
+
+	if let Ok(folders) = vdf::parse_file(&root.join("steamapps/libraryfolders.vdf")) {
+		let list = folders.entries().first().map(|(_, v)| v.entries()).unwrap_or(&[]);
+
+		for (key, value) in list {
+			if key.parse::<u32>().is_err() {
+				continue;
+			}
+
+			// Newer files nest the path in an object, older ones store it directly
+			let path = value.get("path").and_then(Vdf::as_str).or_else(|| value.as_str());
+
+			if let Some(path) = path {
+				let dir = Path::new(path).join("steamapps");
+
+				if !dirs.contains(&dir) {
+					dirs.push(dir);
+				}
+			}
+		}
+	}
+
+	dirs
+}
+
+/// Finds and parses the appmanifest of an installed app
+pub fn find_manifest(config: &Config, id: u32) -> Option<(PathBuf, Vdf)> {
+	library_dirs(config).into_iter()
+		.map(|dir| dir.join(format!("appmanifest_{}.acf", id)))
+		.find(|path| path.is_file())
+		.and_then(|path| vdf::parse_file(&path).ok().map(|manifest| (path, manifest)))
+}
+
+/// The name and type of an app as known to the Steam client
+pub struct CachedApp {
+	pub name: Option<String>,
+	pub app_type: Option<AppType>
+}
+
+/// Looks up an app in the client's local app info cache, which works offline
+/// and for apps that aren't on the store (anymore)
+pub fn cached_app(config: &Config, id: u32) -> Option<CachedApp> {
+	let path = steam_root(config)?.join("appcache/appinfo.vdf");
+	let app = binary_vdf::read_appinfo(&path, id).ok()??;
+
+	Some(CachedApp {
+		name: app.name().map(String::from),
+		app_type: app.app_type().map(|t| AppType::from_store_str(&t.to_lowercase()))
+	})
+}
+
+/// An account that has logged in to the Steam client on this machine
+pub struct LoginUser {
+	pub steam_id: u64,
+	pub account_name: String,
+	pub persona_name: Option<String>,
+	/// Whether this account logged in last
+	pub most_recent: bool,
+	/// When the account last logged in, in seconds since the Unix epoch
+	pub timestamp: u64
+}
+
+/// How the current user was determined
+#[derive(Clone, Copy, PartialEq)]
+pub enum UserSource {
+	Config,
+	AutoLogin,
+	MostRecent
+}
+
+impl std::fmt::Display for UserSource {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		let s = match self {
+			UserSource::Config => "the 'steam_id' config key",
+			UserSource::AutoLogin => "Steam's auto-login user",
+			UserSource::MostRecent => "the most recent login"
+		};
+
+		write!(f, "{}", s)
+	}
+}
+
+/// Lists the accounts in the client's config/loginusers.vdf, most recently
+/// logged in first
+pub fn login_users(config: &Config) -> Vec<LoginUser> {
+	let path = match steam_root(config) {
+		Some(root) => root.join("config/loginusers.vdf"),
+		None => return Vec::new()
+	};
+
+	let users = match vdf::parse_file(&path) {
+		Ok(users) => users,
+		Err(_) => return Vec::new()
+	};
+
+	let mut list: Vec<LoginUser> = users.get("users").map(Vdf::entries).unwrap_or(&[]).iter()
+		.filter_map(|(id, user)| {
+			let field = |key: &str| user.get(key).and_then(Vdf::as_str);
+
+			Some(LoginUser {
+				steam_id: id.parse().ok()?,
+				account_name: field("AccountName")?.to_string(),
+				persona_name: field("PersonaName").map(String::from),
+				// Older clients call it "mostrecent"; keys are case-insensitive
+				most_recent: field("MostRecent") == Some("1"),
+				timestamp: field("Timestamp").and_then(|t| t.parse().ok()).unwrap_or(0)
+			})
+		})
+		.collect();
+
+	list.sort_by_key(|user| std::cmp::Reverse((user.most_recent, user.timestamp)));
+	list
+}
+
+/// Gets the account name the client logs in with automatically, from
+/// ~/.steam/registry.vdf
+fn auto_login_user() -> Option<String> {
+	let registry = vdf::parse_file(&dirs::home_dir()?.join(".steam/registry.vdf")).ok()?;
+
+	registry.get_path(&["Registry", "HKCU", "Software", "Valve", "Steam", "AutoLoginUser"])
+		.and_then(Vdf::as_str)
+		.filter(|name| !name.is_empty())
+		.map(String::from)
+}
+
+/// Finds the current user and how it was found: the 'steam_id' config key
+/// takes precedence over the client's auto-login user, which takes
+/// precedence over the account that logged in most recently
+pub fn find_current_user(config: &Config) -> Option<(u64, UserSource)> {
+	if let Some(id) = config.get_u64("steam_id") {
+		return Some((id, UserSource::Config));
+	}
+
+	let users = login_users(config);
+
+	if let Some(name) = auto_login_user() {
+		if let Some(user) = users.iter().find(|user| user.account_name.eq_ignore_ascii_case(&name)) {
+			return Some((user.steam_id, UserSource::AutoLogin));
+		}
+	}
+
+	users.first().map(|user| (user.steam_id, UserSource::MostRecent))
+}
+
+/// Gets the 64-bit Steam ID of the current user, see 'find_current_user'
+pub fn current_user(config: &Config) -> Option<u64> {
+	find_current_user(config).map(|(id, _)| id)
+}
+
+/// The difference between a 64-bit Steam ID and the 32-bit account ID that
+/// names the user's directory in 'userdata'
+const STEAM_ID_BASE: u64 = 76_561_197_960_265_728;
+
+/// Gets the 'userdata' directory of the current user, which holds per-user
+/// files such as localconfig.vdf and shortcuts.vdf
+pub fn userdata_dir(config: &Config) -> Option<PathBuf> {
+	userdata_dir_of(config, current_user(config)?)
+}
+
+/// Gets the 'userdata' directory of any account by its 64-bit Steam ID
+pub fn userdata_dir_of(config: &Config, steam_id: u64) -> Option<PathBuf> {
+	let account = steam_id.checked_sub(STEAM_ID_BASE)?;
+
+	Some(steam_root(config)?.join("userdata").join(account.to_string()))
+}
+
+/// Whether the Steam client is running
+pub fn is_running() -> bool {
+	fs::read_dir("/proc")
+		.map(|entries| entries.flatten().any(|entry| {
+			fs::read_to_string(entry.path().join("comm"))
+				.map(|comm| comm.trim() == "steam")
+				.unwrap_or(false)
+		}))
+		.unwrap_or(false)
+}
@@ -0,0 +1,179 @@
+use std::fmt;
+use std::io::Write;
+use std::str::FromStr;
+use std::process::{Command, Stdio};
+use serde::{Serialize, Deserialize};
+use serde_json::Value;
+
+/// The kind of a Steam app, as reported by the store
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AppType {
+	Game,
+	Dlc,
+	Demo,
+	Tool,
+	Soundtrack,
+	Other
+}
+
+impl AppType {
+	pub fn from_store_str(s: &str) -> AppType {
+		match s {
+			"game" => AppType::Game,
+			"dlc" => AppType::Dlc,
+			"demo" => AppType::Demo,
+			"tool" | "application" => AppType::Tool,
+			"music" => AppType::Soundtrack,
+			_ => AppType::Other
+		}
+	}
+
+	/// Whether an app of this type can be started with 'steam://run'
+	pub fn is_launchable(self) -> bool {
+		matches!(self, AppType::Game | AppType::Demo | AppType::Tool)
+	}
+}
+
+impl fmt::Display for AppType {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		let s = match self {
+			AppType::Game => "game",
+			AppType::Dlc => "dlc",
+			AppType::Demo => "demo",
+			AppType::Tool => "tool",
+			AppType::Soundtrack => "soundtrack",
+			AppType::Other => "other"
+		};
+
+		write!(f, "{}", s)
+	}
+}
+
+impl FromStr for AppType {
+	type Err = String;
+
+	fn from_str(s: &str) -> Result<AppType, String> {
+		match s.to_lowercase().as_str() {
+			"game" => Ok(AppType::Game),
+			"dlc" => Ok(AppType::Dlc),
+			"demo" => Ok(AppType::Demo),
+			"tool" => Ok(AppType::Tool),
+			"soundtrack" | "music" => Ok(AppType::Soundtrack),
+			"other" => Ok(AppType::Other),
+			_ => Err(format!("Unknown app type '{}' (expected game, dlc, demo, tool, soundtrack or other)", s))
+		}
+	}
+}
+
+/// Quotes a value for a curl config file
+fn curl_quote(s: &str) -> String {
+	format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Fetches a URL via 'curl' with extra headers, returning None if the
+/// request failed
+pub fn fetch(url: &str, headers: &[&str]) -> Option<Vec<u8>> {
+	curl(url, headers, None)
+}
+
+/// Posts a body to a URL via 'curl' with extra headers, returning None if
+/// the request failed
+pub fn post(url: &str, headers: &[&str], body: &str) -> Option<Vec<u8>> {
+	curl(url, headers, Some(body))
+}
+
+/// Runs a request via 'curl', posting the body if there is one
+///
+/// The URL, headers and body are passed as a curl config on stdin rather
+/// than as arguments, since they may hold an API key, a cookie or a webhook
+/// token that other users could see with 'ps'
+fn curl(url: &str, headers: &[&str], body: Option<&str>) -> Option<Vec<u8>> {
+	let mut child = Command::new("curl")
+		.args(["--silent", "--fail", "--location", "--max-time", "5", "--config", "-"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.ok()?;
+
+	let mut curl_config = format!("url = {}\n", curl_quote(url));
+
+	for header in headers {
+		curl_config.push_str(&format!("header = {}\n", curl_quote(header)));
+	}
+
+	if let Some(body) = body {
+		curl_config.push_str(&format!("data-binary = {}\n", curl_quote(body)));
+	}
+
+	child.stdin.take()?.write_all(curl_config.as_bytes()).ok()?;
+
+	let output = child.wait_with_output().ok()?;
+
+	if !output.status.success() {
+		return None;
+	}
+
+	Some(output.stdout)
+}
+
+/// Fetches JSON from a URL via 'curl', returning None if the request failed
+pub fn fetch_json(url: &str) -> Option<Value> {
+	serde_json::from_slice(&fetch(url, &[])?).ok()
+}
+
+/// Fetches the store's 'appdetails' data object for a Steam app ID
+pub fn fetch_app_details(id: u32) -> Option<Value> {
+	let url = format!("https://store.steampowered.com/api/appdetails?appids={}", id);
+	let mut json = fetch_json(&url)?;
+	let entry = json.get_mut(id.to_string())?;
+
+	if entry["success"].as_bool() != Some(true) {
+		return None;
+	}
+
+	Some(entry["data"].take())
+}
+
+/// Fetches the type of a Steam app from the store
+pub fn fetch_app_type(id: u32) -> Option<AppType> {
+	let details = fetch_app_details(id)?;
+
+	details["type"].as_str().map(AppType::from_store_str)
+}
+
+/// A Steam app referred to by an ID or a link
+pub struct AppLink {
+	pub id: u32,
+	/// The name of the app as written in the link, if it has one
+	pub name: Option<String>
+}
+
+/// Parts of links that are followed by an app ID
+static LINK_MARKERS: &[&str] = &["/app/", "s.team/a/", "steam://run/", "steam://rungameid/", "steam://store/", "steam://install/"];
+
+/// Parses a Steam app ID or a link to an app, such as
+/// https://store.steampowered.com/app/227300/Euro_Truck_Simulator_2/
+pub fn parse_app_link(s: &str) -> Option<AppLink> {
+	let s = s.trim();
+
+	if let Ok(id) = s.parse::<u32>() {
+		return Some(AppLink { id, name: None });
+	}
+
+	let (start, marker) = LINK_MARKERS.iter()
+		.filter_map(|marker| s.find(marker).map(|i| (i, marker)))
+		.min()?;
+	let rest = &s[start + marker.len()..];
+
+	let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+	let id = digits.parse().ok()?;
+
+	// Store links are followed by the name, e.g. "/Euro_Truck_Simulator_2/"
+	let name = rest[digits.len()..].strip_prefix('/')
+		.and_then(|slug| slug.split(['/', '?', '#']).next())
+		.map(|slug| slug.replace('_', " ").trim().to_string())
+		.filter(|name| !name.is_empty());
+
+	Some(AppLink { id, name })
+}
@@ -0,0 +1,182 @@
+use std::fs;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::output;
+use crate::session::{self, POLL_INTERVAL};
+
+/// How long the first sample is taken over, before anything is shown
+const FIRST_SAMPLE: Duration = Duration::from_secs(1);
+
+/// A process of the game at one point in time
+struct Process {
+	pid: u32,
+	ppid: u32,
+	command: String,
+	/// CPU time used so far, in clock ticks
+	cpu_ticks: u64,
+	/// Resident memory in bytes
+	memory: u64
+}
+
+impl Process {
+	fn read(pid: u32) -> Option<Process> {
+		let dir = format!("/proc/{}", pid);
+		let stat = fs::read_to_string(format!("{}/stat", dir)).ok()?;
+
+		// The command is in parentheses and may contain spaces, so the other
+		// fields are counted from the closing one
+		let (command, rest) = stat.split_at(stat.rfind(')')?);
+		let command = command.split_once('(')?.1.to_string();
+		let fields: Vec<&str> = rest[1..].split_whitespace().collect();
+
+		let ppid = fields.get(1)?.parse().ok()?;
+		let cpu_ticks = fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?;
+
+		let memory = fs::read_to_string(format!("{}/status", dir)).ok()
+			.and_then(|status| {
+				let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+				line.split_whitespace().nth(1)?.parse::<u64>().ok()
+			})
+			.map(|kib| kib * 1024)
+			.unwrap_or(0);
+
+		Some(Process { pid, ppid, command, cpu_ticks, memory })
+	}
+}
+
+/// Usage of the GPU as a whole, since per-process figures aren't generally available
+struct GpuUsage {
+	busy_percent: Option<u64>,
+	memory_used: u64,
+	memory_total: u64
+}
+
+/// Reads GPU usage through nvidia-smi, or from the amdgpu driver in sysfs
+fn gpu_usage() -> Option<GpuUsage> {
+	let nvidia = Command::new("nvidia-smi")
+		.args(["--query-gpu=utilization.gpu,memory.used,memory.total", "--format=csv,noheader,nounits"])
+		.stderr(Stdio::null())
+		.output()
+		.ok()
+		.filter(|output| output.status.success());
+
+	if let Some(output) = nvidia {
+		let stdout = String::from_utf8_lossy(&output.stdout);
+		let values: Vec<u64> = stdout.lines().next()?.split(',').filter_map(|v| v.trim().parse().ok()).collect();
+
+		if let [busy, used, total] = values[..] {
+			return Some(GpuUsage { busy_percent: Some(busy), memory_used: used * 1024 * 1024, memory_total: total * 1024 * 1024 });
+		}
+	}
+
+	fs::read_dir("/sys/class/drm").ok()?.flatten()
+		.map(|entry| entry.path().join("device"))
+		.find_map(|device| {
+			let read = |file: &str| fs::read_to_string(device.join(file)).ok()?.trim().parse::<u64>().ok();
+			let memory_total = read("mem_info_vram_total")?;
+
+			Some(GpuUsage { busy_percent: read("gpu_busy_percent"), memory_used: read("mem_info_vram_used").unwrap_or(0), memory_total })
+		})
+}
+
+/// Orders processes so that children follow their parent, with their depth
+/// in the tree
+fn tree(processes: &[Process]) -> Vec<(&Process, usize)> {
+	fn add<'a>(processes: &'a [Process], parent: &'a Process, depth: usize, list: &mut Vec<(&'a Process, usize)>) {
+		list.push((parent, depth));
+
+		for child in processes.iter().filter(|p| p.ppid == parent.pid) {
+			add(processes, child, depth + 1, list);
+		}
+	}
+
+	let mut list = Vec::new();
+
+	for root in processes.iter().filter(|p| !processes.iter().any(|other| other.pid == p.ppid)) {
+		add(processes, root, 0, &mut list);
+	}
+
+	list
+}
+
+fn sample(id: u32) -> Vec<Process> {
+	let mut processes: Vec<Process> = session::find_game_processes(id).into_iter().filter_map(Process::read).collect();
+
+	processes.sort_by_key(|p| p.pid);
+	processes
+}
+
+/// Shows the CPU, memory and GPU usage of a running game's processes,
+/// refreshing until the game exits; plain mode prints each refresh as
+/// labeled lines instead of redrawing the screen
+pub fn run(name: &str, id: u32, once: bool, plain: bool) {
+	let ticks_per_second = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+
+	let mut previous = sample(id);
+	let mut sampled = Instant::now();
+
+	if previous.is_empty() {
+		println!("{} is not running", name);
+		return;
+	}
+
+	thread::sleep(FIRST_SAMPLE);
+
+	loop {
+		let processes = sample(id);
+		let elapsed = sampled.elapsed().as_secs_f64();
+		sampled = Instant::now();
+
+		if processes.is_empty() {
+			println!("{} exited", name);
+			return;
+		}
+
+		let cpu = |process: &Process| {
+			let before = previous.iter().find(|p| p.pid == process.pid).map_or(process.cpu_ticks, |p| p.cpu_ticks);
+			process.cpu_ticks.saturating_sub(before) as f64 / ticks_per_second / elapsed * 100.0
+		};
+
+		let total_cpu: f64 = processes.iter().map(cpu).sum();
+		let total_memory: u64 = processes.iter().map(|p| p.memory).sum();
+		let gpu = gpu_usage().map(|gpu| {
+			let busy = gpu.busy_percent.map_or(String::new(), |busy| format!("{}%, ", busy));
+			format!("{}{} / {} VRAM", busy, output::format_size(gpu.memory_used), output::format_size(gpu.memory_total))
+		});
+
+		let mut fields = vec![
+			("Game", format!("{} ({})", name, id)),
+			("Processes", processes.len().to_string()),
+			("CPU", format!("{:.1}%", total_cpu)),
+			("Memory", output::format_size(total_memory))
+		];
+
+		if let Some(gpu) = gpu {
+			fields.push(("GPU", gpu));
+		}
+
+		if plain {
+			output::print_record(&fields);
+		} else {
+			// Clears the screen and moves the cursor to the top
+			print!("\x1b[2J\x1b[H");
+			output::print_fields(&fields, false);
+			println!("\n{:>8} {:>7} {:>10}  COMMAND", "PID", "CPU%", "MEMORY");
+
+			for (process, depth) in tree(&processes) {
+				let branch = if depth > 0 { format!("{}└ ", "  ".repeat(depth - 1)) } else { String::new() };
+
+				println!("{:>8} {:>6.1}% {:>10}  {}{}", process.pid, cpu(process), output::format_size(process.memory), branch, process.command);
+			}
+		}
+
+		if once {
+			return;
+		}
+
+		previous = processes;
+		thread::sleep(POLL_INTERVAL);
+	}
+}
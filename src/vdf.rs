@@ -0,0 +1,424 @@
+//! A parser for Steam's text VDF (KeyValues) format, which is used by
+//! appmanifest_<id>.acf, libraryfolders.vdf, loginusers.vdf and others
+//!
+//! ```no_run
+//! use std::path::Path;
+//! use steamlet::vdf::{self, Vdf};
+//!
+//! let manifest = vdf::parse_file(Path::new("appmanifest_227300.acf")).unwrap();
+//! let build = manifest.get_path(&["AppState", "buildid"]).and_then(Vdf::as_str);
+//! ```
+
+use std::fs;
+use std::path::Path;
+
+/// A node of a text VDF (KeyValues) document, such as an appmanifest
+#[derive(Debug, Clone, PartialEq)]
+pub enum Vdf {
+	Str(String),
+	Map(Vec<(String, Vdf)>)
+}
+
+impl Vdf {
+	/// Gets a child by key; keys in VDF files are case-insensitive
+	pub fn get(&self, key: &str) -> Option<&Vdf> {
+		match self {
+			Vdf::Map(entries) => entries.iter()
+				.find(|(k, _)| k.eq_ignore_ascii_case(key))
+				.map(|(_, v)| v),
+			Vdf::Str(_) => None
+		}
+	}
+
+	/// Follows a path of keys, e.g. `["AppState", "buildid"]`
+	pub fn get_path(&self, path: &[&str]) -> Option<&Vdf> {
+		path.iter().try_fold(self, |node, key| node.get(key))
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Vdf::Str(s) => Some(s),
+			Vdf::Map(_) => None
+		}
+	}
+
+	pub fn entries(&self) -> &[(String, Vdf)] {
+		match self {
+			Vdf::Map(entries) => entries,
+			Vdf::Str(_) => &[]
+		}
+	}
+
+	/// Gets a child by key to change it
+	pub fn get_mut(&mut self, key: &str) -> Option<&mut Vdf> {
+		match self {
+			Vdf::Map(entries) => entries.iter_mut()
+				.find(|(k, _)| k.eq_ignore_ascii_case(key))
+				.map(|(_, v)| v),
+			Vdf::Str(_) => None
+		}
+	}
+
+	/// Gets a child of a map by key, adding an empty map for it if it's missing
+	pub fn entry(&mut self, key: &str) -> Option<&mut Vdf> {
+		if self.get(key).is_none() {
+			self.insert(key, Vdf::Map(Vec::new()));
+		}
+
+		self.get_mut(key)
+	}
+
+	/// Sets a child of a map, replacing an existing one with the same key
+	pub fn insert(&mut self, key: &str, value: Vdf) {
+		if let Vdf::Map(entries) = self {
+			match entries.iter_mut().find(|(k, _)| k.eq_ignore_ascii_case(key)) {
+				Some(entry) => entry.1 = value,
+				None => entries.push((key.to_string(), value))
+			}
+		}
+	}
+
+	/// Removes a child of a map, returning it if it existed
+	pub fn remove(&mut self, key: &str) -> Option<Vdf> {
+		match self {
+			Vdf::Map(entries) => {
+				let index = entries.iter().position(|(k, _)| k.eq_ignore_ascii_case(key))?;
+				Some(entries.remove(index).1)
+			},
+			Vdf::Str(_) => None
+		}
+	}
+}
+
+/// Parses a text VDF document into a map of its top-level entries
+pub fn parse(text: &str) -> Result<Vdf, String> {
+	let mut parser = Parser { chars: text.chars().collect(), pos: 0, line: 1, exact: false };
+	let entries = parser.parse_entries(true)?;
+
+	Ok(Vdf::Map(entries))
+}
+
+/// Parses a text VDF document that is going to be written back, failing on
+/// anything that `to_string` can't keep: comments, platform conditionals and
+/// unknown escapes
+pub fn parse_exact(text: &str) -> Result<Vdf, String> {
+	let mut parser = Parser { chars: text.chars().collect(), pos: 0, line: 1, exact: true };
+	let entries = parser.parse_entries(true)?;
+
+	Ok(Vdf::Map(entries))
+}
+
+/// Reads and parses a text VDF file
+pub fn parse_file(path: &Path) -> Result<Vdf, String> {
+	let text = fs::read_to_string(path)
+		.map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+
+	parse(&text).map_err(|e| format!("Could not parse {}: {}", path.display(), e))
+}
+
+/// Formats the top-level entries of a map as a text VDF document, indented
+/// with tabs like Steam writes them
+///
+/// Comments and platform conditionals of a parsed document are not kept
+pub fn to_string(vdf: &Vdf) -> String {
+	let mut text = String::new();
+
+	write_entries(&mut text, vdf.entries(), 0);
+	text
+}
+
+/// Writes a text VDF file, replacing the old one only once the new one has
+/// been written completely
+///
+/// Nothing is written unless the text reads back as the same document
+pub fn write_file(path: &Path, vdf: &Vdf) -> Result<(), String> {
+	let text = to_string(vdf);
+
+	if parse_exact(&text).as_ref() != Ok(vdf) {
+		return Err(format!("Could not write {}: the document would not read back the same", path.display()));
+	}
+
+	let mut temp = path.as_os_str().to_owned();
+	temp.push(".tmp");
+
+	fs::write(&temp, text)
+		.and_then(|_| fs::rename(&temp, path))
+		.map_err(|e| format!("Could not write {}: {}", path.display(), e))
+}
+
+fn write_entries(text: &mut String, entries: &[(String, Vdf)], depth: usize) {
+	let indent = "\t".repeat(depth);
+
+	for (key, value) in entries {
+		match value {
+			Vdf::Str(s) => text.push_str(&format!("{}\"{}\"\t\t\"{}\"\n", indent, escape(key), escape(s))),
+			Vdf::Map(children) => {
+				text.push_str(&format!("{}\"{}\"\n{}{{\n", indent, escape(key), indent));
+				write_entries(text, children, depth + 1);
+				text.push_str(&format!("{}}}\n", indent));
+			}
+		}
+	}
+}
+
+fn escape(s: &str) -> String {
+	s.replace('\\', "\\\\")
+		.replace('"', "\\\"")
+		.replace('\n', "\\n")
+		.replace('\t', "\\t")
+}
+
+enum Token {
+	Str(String),
+	Open,
+	Close
+}
+
+struct Parser {
+	chars: Vec<char>,
+	pos: usize,
+	line: usize,
+	/// Whether to fail on what would be lost when writing the document back
+	exact: bool
+}
+
+impl Parser {
+	fn parse_entries(&mut self, top_level: bool) -> Result<Vec<(String, Vdf)>, String> {
+		let mut entries = Vec::new();
+
+		loop {
+			let key = match self.next_token()? {
+				Some(Token::Str(key)) => key,
+				Some(Token::Close) if !top_level => return Ok(entries),
+				None if top_level => return Ok(entries),
+				Some(Token::Open) | Some(Token::Close) => return Err(self.error("expected a key")),
+				None => return Err(self.error("unexpected end of file"))
+			};
+
+			let value = match self.next_token()? {
+				Some(Token::Str(value)) => Vdf::Str(value),
+				Some(Token::Open) => Vdf::Map(self.parse_entries(false)?),
+				_ => return Err(self.error(&format!("expected a value for '{}'", key)))
+			};
+
+			entries.push((key, value));
+		}
+	}
+
+	fn next_token(&mut self) -> Result<Option<Token>, String> {
+		loop {
+			self.skip_whitespace();
+
+			match self.peek() {
+				None => return Ok(None),
+				Some('{') => {
+					self.pos += 1;
+					return Ok(Some(Token::Open));
+				},
+				Some('}') => {
+					self.pos += 1;
+					return Ok(Some(Token::Close));
+				},
+				Some('/') if self.chars.get(self.pos + 1) == Some(&'/') => {
+					if self.exact {
+						return Err(self.error("unexpected comment"));
+					}
+
+					self.skip_line();
+				},
+				// Platform conditionals such as [$WIN32] are ignored
+				Some('[') => {
+					if self.exact {
+						return Err(self.error("unexpected platform conditional"));
+					}
+
+					self.skip_until(']');
+				},
+				Some('"') => return self.quoted().map(|s| Some(Token::Str(s))),
+				Some(_) => return Ok(Some(Token::Str(self.unquoted())))
+			}
+		}
+	}
+
+	fn quoted(&mut self) -> Result<String, String> {
+		let mut s = String::new();
+		self.pos += 1;
+
+		loop {
+			match self.bump() {
+				None => return Err(self.error("unterminated string")),
+				Some('"') => return Ok(s),
+				Some('\\') => match self.bump() {
+					Some('n') => s.push('\n'),
+					Some('t') => s.push('\t'),
+					Some(c) if self.exact && c != '\\' && c != '"' => {
+						return Err(self.error(&format!("unknown escape '\\{}'", c)));
+					},
+					Some(c) => s.push(c),
+					None => return Err(self.error("unterminated string"))
+				},
+				Some(c) => s.push(c)
+			}
+		}
+	}
+
+	fn unquoted(&mut self) -> String {
+		let mut s = String::new();
+
+		while let Some(c) = self.peek() {
+			if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+				break;
+			}
+
+			s.push(c);
+			self.pos += 1;
+		}
+
+		s
+	}
+
+	fn skip_whitespace(&mut self) {
+		while let Some(c) = self.peek() {
+			if !c.is_whitespace() {
+				break;
+			}
+
+			self.bump();
+		}
+	}
+
+	fn skip_line(&mut self) {
+		self.skip_until('\n');
+	}
+
+	fn skip_until(&mut self, end: char) {
+		while let Some(c) = self.bump() {
+			if c == end {
+				break;
+			}
+		}
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.chars.get(self.pos).copied()
+	}
+
+	fn bump(&mut self) -> Option<char> {
+		let c = self.peek()?;
+		self.pos += 1;
+
+		if c == '\n' {
+			self.line += 1;
+		}
+
+		Some(c)
+	}
+
+	fn error(&self, message: &str) -> String {
+		format!("{} on line {}", message, self.line)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn str(s: &str) -> Vdf {
+		Vdf::Str(s.to_string())
+	}
+
+	#[test]
+	fn parses_nested_maps() {
+		let vdf = parse("\"AppState\"\n{\n\t\"appid\"\t\t\"227300\"\n\t\"UserConfig\"\n\t{\n\t\t\"language\"\t\t\"english\"\n\t}\n}\n").unwrap();
+
+		assert_eq!(vdf.get_path(&["AppState", "appid"]), Some(&str("227300")));
+		assert_eq!(vdf.get_path(&["appstate", "userconfig", "LANGUAGE"]), Some(&str("english")));
+		assert_eq!(vdf.get_path(&["AppState", "missing"]), None);
+	}
+
+	#[test]
+	fn parses_escapes() {
+		let vdf = parse(r#""path" "C:\\Games\\\"Steam\"" "text" "a\tb\nc""#).unwrap();
+
+		assert_eq!(vdf.get("path"), Some(&str("C:\\Games\\\"Steam\"")));
+		assert_eq!(vdf.get("text"), Some(&str("a\tb\nc")));
+	}
+
+	#[test]
+	fn parses_unquoted_tokens() {
+		let vdf = parse("key value\nmap{inner 1}").unwrap();
+
+		assert_eq!(vdf.get("key"), Some(&str("value")));
+		assert_eq!(vdf.get_path(&["map", "inner"]), Some(&str("1")));
+	}
+
+	#[test]
+	fn skips_comments() {
+		let vdf = parse("// a comment\n\"a\" \"1\" // trailing comment\n// \"b\" \"2\"\n").unwrap();
+
+		assert_eq!(vdf, Vdf::Map(vec![("a".to_string(), str("1"))]));
+	}
+
+	#[test]
+	fn ignores_conditionals() {
+		let vdf = parse("\"a\"\t\"1\"\t[$WIN32]\n\"b\"\t\"2\"\t[!$OSX]\n").unwrap();
+
+		assert_eq!(vdf, Vdf::Map(vec![("a".to_string(), str("1")), ("b".to_string(), str("2"))]));
+	}
+
+	#[test]
+	fn keeps_duplicate_keys() {
+		let vdf = parse("\"a\" \"1\"\n\"A\" \"2\"\n").unwrap();
+
+		assert_eq!(vdf.entries().len(), 2);
+		// Lookups find the first
+		assert_eq!(vdf.get("a"), Some(&str("1")));
+	}
+
+	#[test]
+	fn fails_on_unterminated_strings() {
+		assert_eq!(parse("\"a\" \"1").unwrap_err(), "unterminated string on line 1");
+		assert_eq!(parse("\"a\"\n\"1\\").unwrap_err(), "unterminated string on line 2");
+	}
+
+	#[test]
+	fn writes_documents_that_read_back_the_same() {
+		let vdf = Vdf::Map(vec![
+			("UserLocalConfigStore".to_string(), Vdf::Map(vec![
+				("LaunchOptions".to_string(), str("-novid +exec \"my config.cfg\" %command%")),
+				("path".to_string(), str("C:\\Games\t\\Steam\nnext line")),
+				("empty".to_string(), Vdf::Map(Vec::new()))
+			])),
+			("other".to_string(), str(""))
+		]);
+		let text = to_string(&vdf);
+
+		assert_eq!(text, "\"UserLocalConfigStore\"\n{\n\t\"LaunchOptions\"\t\t\"-novid +exec \\\"my config.cfg\\\" %command%\"\n\t\"path\"\t\t\"C:\\\\Games\\t\\\\Steam\\nnext line\"\n\t\"empty\"\n\t{\n\t}\n}\n\"other\"\t\t\"\"\n");
+		assert_eq!(parse_exact(&text), Ok(vdf.clone()));
+		assert_eq!(parse(&text), Ok(vdf));
+	}
+
+	#[test]
+	fn rewrites_what_steam_wrote_unchanged() {
+		let text = "\"apps\"\n{\n\t\"227300\"\n\t{\n\t\t\"LaunchOptions\"\t\t\"-nointro \\\"quoted\\\"\"\n\t}\n}\n";
+
+		assert_eq!(to_string(&parse_exact(text).unwrap()), text);
+	}
+
+	#[test]
+	fn exact_parsing_fails_on_what_would_be_lost() {
+		assert_eq!(parse_exact("\"a\"\t\"1\"\t[$WIN32]\n").unwrap_err(), "unexpected platform conditional on line 1");
+		assert_eq!(parse_exact("\"a\" \"1\"\n// comment\n").unwrap_err(), "unexpected comment on line 2");
+		assert_eq!(parse_exact("\"a\" \"C:\\Games\"").unwrap_err(), "unknown escape '\\G' on line 1");
+
+		// Without exact parsing, conditionals and comments are simply dropped
+		assert_eq!(to_string(&parse("\"a\"\t\"1\"\t[$WIN32] // comment\n").unwrap()), "\"a\"\t\t\"1\"\n");
+	}
+
+	#[test]
+	fn fails_on_unbalanced_braces() {
+		assert_eq!(parse("\"a\"\n{\n\"b\" \"1\"\n").unwrap_err(), "unexpected end of file on line 4");
+		assert_eq!(parse("}").unwrap_err(), "expected a key on line 1");
+		assert_eq!(parse("\"a\"").unwrap_err(), "expected a value for 'a' on line 1");
+	}
+}
@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::PathBuf;
+use serde::{Serialize, Deserialize};
+
+use crate::config::Config;
+use crate::data;
+use crate::notify;
+use crate::output;
+use crate::store;
+
+static WATCH_FILE_NAME: &str = "watchlist.json";
+
+/// A game whose price is watched
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Watch {
+	pub id: u32,
+	pub name: String,
+
+	/// Alert when the price is at or below this many cents; any discount
+	/// alerts otherwise
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub below: Option<u64>,
+
+	/// The price of the last alert, so every sale is only alerted once
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub alerted_price: Option<u64>
+}
+
+pub struct Watchlist {
+	pub watches: Vec<Watch>
+}
+
+/// The current price of a game in the store
+pub struct Price {
+	/// The price in cents, after any discount
+	pub cents: u64,
+	pub discount_percent: u64,
+	pub formatted: String
+}
+
+/// Parses a price such as "9.99" into cents
+pub fn parse_price(s: &str) -> Result<u64, String> {
+	match s.trim().parse::<f64>() {
+		Ok(price) if price >= 0.0 => Ok((price * 100.0).round() as u64),
+		_ => Err(format!("Invalid price '{}'", s))
+	}
+}
+
+fn format_cents(cents: u64) -> String {
+	format!("{}.{:02}", cents / 100, cents % 100)
+}
+
+/// Fetches the current price of a game, in the currency of the 'country'
+/// config value if set
+pub fn fetch_price(config: &Config, id: u32) -> Option<Price> {
+	let mut url = format!("https://store.steampowered.com/api/appdetails?appids={}&filters=price_overview", id);
+
+	if let Some(country) = config.get_str("country") {
+		url += &format!("&cc={}", country);
+	}
+
+	let json = store::fetch_json(&url)?;
+	let price = &json[id.to_string()]["data"]["price_overview"];
+
+	Some(Price {
+		cents: price["final"].as_u64()?,
+		discount_percent: price["discount_percent"].as_u64().unwrap_or(0),
+		formatted: price["final_formatted"].as_str().unwrap_or_default().to_string()
+	})
+}
+
+impl Watchlist {
+	pub fn path() -> PathBuf {
+		data::data_dir().join(WATCH_FILE_NAME)
+	}
+
+	pub fn load() -> Watchlist {
+		let watches = fs::read_to_string(Watchlist::path())
+			.ok()
+			.and_then(|text| serde_json::from_str(&text).ok())
+			.unwrap_or_default();
+
+		Watchlist { watches }
+	}
+
+	pub fn save(&self, message: String) {
+		match fs::write(Watchlist::path(), serde_json::to_string_pretty(&self.watches).unwrap()) {
+			Ok(_) => if !message.is_empty() {
+				println!("{}", message);
+			},
+			Err(_) => println!("Error while writing to {}", WATCH_FILE_NAME)
+		}
+	}
+
+	/// Starts watching a game, replacing its threshold if it is already watched
+	pub fn add(&mut self, id: u32, name: String, below: Option<u64>) {
+		self.watches.retain(|w| w.id != id);
+		self.watches.push(Watch { id, name: name.clone(), below, alerted_price: None });
+
+		let threshold = match below {
+			Some(cents) => format!("at or below {}", format_cents(cents)),
+			None => "on sale".to_string()
+		};
+
+		self.save(format!("Watching {} ({}) for when it is {}; total watched = {}", name, id, threshold, self.watches.len()));
+	}
+
+	pub fn remove(&mut self, id: u32) {
+		let count = self.watches.len();
+		self.watches.retain(|w| w.id != id);
+
+		if self.watches.len() == count {
+			println!("{} is not watched", id);
+		} else {
+			self.save(format!("Stopped watching {}; total watched = {}", id, self.watches.len()));
+		}
+	}
+
+	pub fn list(&self, plain: bool) {
+		for watch in &self.watches {
+			let threshold = match watch.below {
+				Some(cents) => format!("below {}", format_cents(cents)),
+				None => "any sale".to_string()
+			};
+
+			if plain {
+				output::print_record(&[("Game", watch.name.clone()), ("ID", watch.id.to_string()), ("Alert", threshold)]);
+			} else {
+				println!("{}\t{}\t{}", watch.name, watch.id, threshold);
+			}
+		}
+	}
+
+	/// Checks every watched price, alerting through a desktop notification
+	/// and the 'watch.webhook' config value when one meets its threshold
+	pub fn check(&mut self, config: &Config) {
+		let webhook = config.get_str("watch.webhook");
+
+		for watch in &mut self.watches {
+			let price = match fetch_price(config, watch.id) {
+				Some(price) => price,
+				None => {
+					println!("{}: could not fetch the price (free or unavailable?)", watch.name);
+					continue;
+				}
+			};
+
+			let hit = match watch.below {
+				Some(cents) => price.cents <= cents,
+				None => price.discount_percent > 0
+			};
+
+			let discount = match price.discount_percent {
+				0 => String::new(),
+				percent => format!(" (-{}%)", percent)
+			};
+
+			println!("{}: {}{}", watch.name, price.formatted, discount);
+
+			if !hit {
+				watch.alerted_price = None;
+				continue;
+			}
+
+			if watch.alerted_price == Some(price.cents) {
+				continue;
+			}
+
+			// A price threshold can be met without a discount
+			let message = match price.discount_percent {
+				0 => format!("{} is {}, at or below your threshold of {}: https://store.steampowered.com/app/{}/", watch.name, price.formatted, format_cents(watch.below.unwrap_or(price.cents)), watch.id),
+				percent => format!("{} is on sale for {} (-{}%): https://store.steampowered.com/app/{}/", watch.name, price.formatted, percent, watch.id)
+			};
+			println!("{}", message);
+
+			notify::desktop(if price.discount_percent > 0 { "Steam sale" } else { "Steam price alert" }, &message);
+
+			if let Some(url) = webhook {
+				if !notify::webhook(url, &message) {
+					println!("Could not post to the webhook");
+				}
+			}
+
+			watch.alerted_price = Some(price.cents);
+		}
+
+		self.save(String::new());
+	}
+}
@@ -0,0 +1,41 @@
+use serde_json::Value;
+
+use crate::config::Config;
+use crate::store;
+
+/// Calls a Steam Web API method, returning the contents of its 'response'
+/// object; the 'api_key' from the config is passed along when it is set
+pub fn call(config: &Config, method: &str, params: &[(&str, String)]) -> Option<Value> {
+	let mut url = format!("https://api.steampowered.com/{}/?format=json", method);
+
+	if let Some(key) = config.get_str("api_key") {
+		url += &format!("&key={}", key);
+	}
+
+	for (name, value) in params {
+		url += &format!("&{}={}", name, value);
+	}
+
+	let mut json = store::fetch_json(&url)?;
+
+	Some(json["response"].take())
+}
+
+/// A player's public profile summary
+pub struct PlayerSummary {
+	pub name: String,
+	/// The name of the game the player is currently in, if any
+	pub playing: Option<String>
+}
+
+pub fn player_summary(config: &Config, steam_id: u64) -> Option<PlayerSummary> {
+	let response = call(config, "ISteamUser/GetPlayerSummaries/v2", &[("steamids", steam_id.to_string())])?;
+	let player = response["players"].get(0)?;
+
+	Some(PlayerSummary {
+		name: player["personaname"].as_str().unwrap_or("unknown").to_string(),
+		playing: player["gameid"].as_str().map(|game_id| {
+			player["gameextrainfo"].as_str().unwrap_or(game_id).to_string()
+		})
+	})
+}
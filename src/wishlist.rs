@@ -0,0 +1,80 @@
+use crate::config::Config;
+use crate::data::{self, Alias, AliasData};
+use crate::steam;
+use crate::store::{self, AppType};
+use crate::webapi;
+
+/// Fetches the app IDs on a user's wishlist
+fn fetch_wishlist(config: &Config, steam_id: u64) -> Option<Vec<u32>> {
+	let response = webapi::call(config, "IWishlistService/GetWishlist/v1", &[("steamid", steam_id.to_string())])?;
+	let items = response["items"].as_array().cloned().unwrap_or_default();
+
+	Some(items.iter()
+		.filter_map(|item| item["appid"].as_u64())
+		.map(|id| id as u32)
+		.collect())
+}
+
+/// Creates dormant aliases, marked as not owned, for every wishlisted game
+/// that doesn't have an alias yet
+pub fn import(config: &Config, data: &mut AliasData, steam_id: Option<u64>) {
+	let steam_id = match steam_id.or_else(|| steam::current_user(config)) {
+		Some(steam_id) => steam_id,
+		None => {
+			println!("No Steam ID given; set one with 'steamlet config steam_id <id>' or use '--steam-id'");
+			return;
+		}
+	};
+
+	let ids = match fetch_wishlist(config, steam_id) {
+		Some(ids) => ids,
+		None => {
+			println!("Could not fetch the wishlist of {}; is the profile public?", steam_id);
+			return;
+		}
+	};
+
+	let mut imported: Vec<String> = Vec::new();
+
+	for id in ids {
+		if data.aliases.values().any(|alias| alias.id == id) {
+			continue;
+		}
+
+		let details = match store::fetch_app_details(id) {
+			Some(details) => details,
+			None => {
+				println!("Could not fetch store details for {}; skipping", id);
+				continue;
+			}
+		};
+
+		let name = details["name"].as_str().unwrap_or_default().to_string();
+
+		// Names that leave nothing to make an alias of are imported by ID
+		let key = match data::alias_from_name(&name) {
+			key if key.is_empty() => id.to_string(),
+			key => key
+		};
+
+		if data.aliases.contains_key(&key) {
+			println!("Alias '{}' already exists; skipping {} ({})", key, name, id);
+			continue;
+		}
+
+		let mut alias = Alias::new(id);
+		alias.app_type = details["type"].as_str().map(AppType::from_store_str);
+		alias.name = Some(name);
+		alias.not_owned = true;
+
+		println!("Imported '{}' ({})", key, id);
+		data.aliases.insert(key.clone(), alias);
+		imported.push(key);
+	}
+
+	if imported.is_empty() {
+		println!("Nothing to be imported; total aliases = {}", data.aliases.len());
+	} else {
+		data.save(format!("{} wishlist aliases successfully imported; total aliases = {}", imported.len(), data.aliases.len()));
+	}
+}